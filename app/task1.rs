@@ -6,7 +6,7 @@ struct Args(String, usize);
 
 fn parse_args() -> Args {
     let usage = "USAGE: <input file or text> <window size>";
-    let mut args = std::env::args().into_iter().skip(1);
+    let mut args = std::env::args().skip(1);
 
     Args(
         args.next().expect(usage),
@@ -23,7 +23,7 @@ fn main() {
         .collect::<String>();
 
     for (el, n) in security::frequencies(&filtered_text.to_lowercase(), utils::is_russian_char, size) {
-        if let Err(_) = writeln!(stdout(), "{} - {:.6}", el, n) {
+        if writeln!(stdout(), "{} - {:.6}", el, n).is_err() {
             break;
         }
     }
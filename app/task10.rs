@@ -0,0 +1,64 @@
+use security::*;
+use security::classical::{self, Alphabet};
+
+struct Args(OperationMode, String, Alphabet, String, String, Option<String>);
+
+fn parse_args() -> Args {
+    let usage = "USAGE: <operation mode> <cipher: caesar, affine or vigenere> <alphabet: ru, en or letters> \
+                 <key: shift, a,b or word> <input file or text> [output file]";
+    let mut args = std::env::args().skip(1);
+
+    Args(
+        args.next().expect(usage).parse().expect("Can't parse OperationMode"),
+        args.next().expect(usage),
+        args.next().expect(usage).parse().expect("Can't parse alphabet"),
+        args.next().expect(usage),
+        args.next().expect(usage),
+        args.next(),
+    )
+}
+
+fn parse_number(s: &str) -> Result<usize, AlgorithmError> {
+    s.trim().parse().map_err(|_| AlgorithmError { what: format!("Can't parse key: {}", s) })
+}
+
+fn run(mode: OperationMode, cipher: &str, alphabet: &Alphabet, key: &str, text: &str) -> Result<String, AlgorithmError> {
+    match cipher.to_lowercase().as_str() {
+        "caesar" => {
+            let shift = parse_number(key)?;
+            Ok(match mode {
+                OperationMode::Encrypt => classical::caesar::encrypt(text, alphabet, shift),
+                OperationMode::Decrypt => classical::caesar::decrypt(text, alphabet, shift),
+            })
+        },
+        "affine" => {
+            let (a, b) = key.split_once(',').ok_or_else(|| AlgorithmError {
+                what: "Affine key must be given as a,b".to_string()
+            })?;
+            let (a, b) = (parse_number(a)?, parse_number(b)?);
+            match mode {
+                OperationMode::Encrypt => classical::affine::encrypt(text, alphabet, a, b),
+                OperationMode::Decrypt => classical::affine::decrypt(text, alphabet, a, b),
+            }
+        },
+        "vigenere" => match mode {
+            OperationMode::Encrypt => classical::vigenere::encrypt(text, alphabet, key),
+            OperationMode::Decrypt => classical::vigenere::decrypt(text, alphabet, key),
+        },
+        other => Err(AlgorithmError { what: format!("Unknown cipher: {}", other) }),
+    }
+}
+
+fn main() {
+    let Args(mode, cipher, alphabet, key, input, output_file) = parse_args();
+
+    let text = std::fs::read_to_string(&input).unwrap_or(input);
+
+    match run(mode, &cipher, &alphabet, &key, &text) {
+        Ok(output) => match output_file {
+            Some(filename) => { std::fs::write(filename, output.as_bytes()).expect("Can't write output file"); },
+            None => println!("{}", output),
+        },
+        Err(AlgorithmError { what }) => eprintln!("AlgorithmError: {}", what),
+    };
+}
@@ -0,0 +1,58 @@
+use security::classical::{self, Alphabet};
+use security::cryptanalysis::{self, vigenere};
+
+struct Args(Alphabet, String, Option<String>, usize);
+
+fn parse_args() -> Args {
+    let usage = "USAGE: <alphabet: ru, en or letters> <input file or text> [reference corpus file] [max period]";
+    let mut args = std::env::args().skip(1);
+
+    Args(
+        args.next().expect(usage).parse().expect("Can't parse alphabet"),
+        args.next().expect(usage),
+        args.next(),
+        args.next().map_or(20, |s| s.parse().expect("Can't parse max period")),
+    )
+}
+
+fn main() {
+    let Args(alphabet, input, corpus, max_period) = parse_args();
+
+    let text = std::fs::read_to_string(&input).unwrap_or(input);
+
+    let reference = match corpus {
+        Some(filename) => cryptanalysis::letter_frequencies(
+            &std::fs::read_to_string(filename).expect("Can't read reference corpus"), &alphabet
+        ),
+        None => cryptanalysis::builtin_frequencies(&alphabet)
+            .expect("No built-in frequencies for this alphabet, please provide reference corpus"),
+    };
+
+    let analysis = match vigenere::analyze(&text, &alphabet, &reference, max_period) {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            eprintln!("AlgorithmError: {}", e.what);
+            return;
+        }
+    };
+
+    println!("Kasiski examination (period - repeated trigram distances divisible by it):");
+    for (period, count) in analysis.kasiski.iter().take(5) {
+        println!("  {} - {}", period, count);
+    }
+    println!("Friedman test estimate: {:.2}", analysis.friedman);
+    println!("Average column index of coincidence:");
+    for (period, ic) in analysis.column_ic.iter() {
+        println!("  {} - {:.4}", period, ic);
+    }
+    println!("Chosen period: {}", analysis.period);
+
+    println!("Key letters (letter - chi-squared - confidence):");
+    for key_letter in analysis.key.iter() {
+        println!("  {} - {:.2} - {:.3}", key_letter.letter, key_letter.chi_squared, key_letter.confidence);
+    }
+
+    let key = analysis.key_string();
+    println!("Key: {}", key);
+    println!("{}", classical::vigenere::decrypt(&text, &alphabet, &key).unwrap());
+}
@@ -0,0 +1,49 @@
+use security::classical::{self, Alphabet};
+use security::cryptanalysis::{self, substitution};
+
+struct Args(Alphabet, String, String, usize, u64, Option<String>);
+
+fn parse_args() -> Args {
+    let usage = "USAGE: <alphabet: ru, en or letters> <input file or text> <reference corpus file> \
+                 [n-gram size] [seed] [output file]";
+    let mut args = std::env::args().skip(1);
+
+    Args(
+        args.next().expect(usage).parse().expect("Can't parse alphabet"),
+        args.next().expect(usage),
+        args.next().expect(usage),
+        args.next().map_or(4, |s| s.parse().expect("Can't parse n-gram size")),
+        args.next().map_or(0, |s| s.parse().expect("Can't parse seed")),
+        args.next(),
+    )
+}
+
+fn main() {
+    let Args(alphabet, input, corpus, n, seed, output_file) = parse_args();
+
+    let text = std::fs::read_to_string(&input).unwrap_or(input);
+    let corpus = std::fs::read_to_string(corpus).expect("Can't read reference corpus");
+
+    let reference = cryptanalysis::letter_frequencies(&corpus, &alphabet);
+    let params = substitution::SolverParams { seed, ..Default::default() };
+
+    let solution = substitution::NgramModel::from_corpus(&corpus, &alphabet, n)
+        .and_then(|model| substitution::solve(&text, &alphabet, &model, &reference, &params, |p| {
+            eprintln!(
+                "restart {}/{}: score {:.4}, best score {:.4}, best key {}",
+                p.restart + 1, params.restarts, p.score, p.best_score, p.best_key
+            )
+        }));
+
+    match solution {
+        Ok(solution) => {
+            let output = classical::substitution::decrypt(&text, &alphabet, &solution.key).unwrap();
+            eprintln!("Key: {}", solution.key);
+            match output_file {
+                Some(filename) => { std::fs::write(filename, output.as_bytes()).expect("Can't write output file"); },
+                None => println!("{}", output),
+            }
+        },
+        Err(e) => eprintln!("AlgorithmError: {}", e.what),
+    }
+}
@@ -0,0 +1,64 @@
+use security::*;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+enum Command {
+    Generate(PathBuf, u64),
+    Apply(OperationMode, PathBuf, String, Option<String>),
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: generate <pad file> <size in bytes>\n       \
+                 <operation mode> <pad file> <input file or text> [output file]";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    if command == "generate" {
+        Command::Generate(
+            args.next().expect(usage).into(),
+            args.next().expect(usage).parse().expect("Can't parse pad size"),
+        )
+    } else {
+        Command::Apply(
+            command.parse().expect("Can't parse OperationMode"),
+            args.next().expect(usage).into(),
+            args.next().expect(usage),
+            args.next(),
+        )
+    }
+}
+
+fn main() {
+    match parse_args() {
+        Command::Generate(pad_path, size) => {
+            let file = File::create(&pad_path).expect("Can't create pad file");
+            otp::generate_pad(file, size).expect("Can't generate pad");
+            // ranges consumed from the previous pad at this path are meaningless now
+            otp::Ledger::default().save(&otp::ledger_path(&pad_path)).expect("Can't write ledger");
+            println!("Generated {} bytes of pad in {:?}", size, pad_path);
+        },
+        Command::Apply(mode, pad_path, input, output_file) => {
+            let data = std::fs::read(&input).unwrap_or(input.into_bytes());
+            let mut pad = File::open(&pad_path).expect("Can't open pad file");
+            let ledger_path = otp::ledger_path(&pad_path);
+
+            let result = otp::Ledger::load(&ledger_path).and_then(|mut ledger| {
+                let output = match mode {
+                    OperationMode::Encrypt => otp::encrypt(&mut pad, &mut ledger, &data),
+                    OperationMode::Decrypt => otp::decrypt(&mut pad, &mut ledger, &data),
+                }?;
+                ledger.save(&ledger_path).expect("Can't write ledger");
+                Ok(output)
+            });
+
+            match result {
+                Ok(output) => match output_file {
+                    Some(filename) => { std::fs::write(filename, &output).expect("Can't write output file"); },
+                    None => println!("{}", String::from_utf8_lossy(&output)),
+                },
+                Err(AlgorithmError { what }) => eprintln!("AlgorithmError: {}", what),
+            }
+        },
+    }
+}
@@ -0,0 +1,101 @@
+use security::*;
+
+enum Padding {
+    Pkcs1,
+    Oaep,
+}
+
+enum Command {
+    Keygen(usize, String, String),
+    Encrypt(String, String, String, Padding),
+    Decrypt(String, String, Option<String>, Padding),
+    Sign(String, String, String),
+    Verify(String, String, String),
+}
+
+fn parse_padding(name: Option<String>) -> Padding {
+    match name.as_deref() {
+        None | Some("oaep") => Padding::Oaep,
+        Some("pkcs1") => Padding::Pkcs1,
+        Some(other) => panic!("Unknown padding {}, expected pkcs1 or oaep", other),
+    }
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: keygen <bits> <private key file> <public key file>\n       \
+                 encrypt <public key file> <input file or text> <output file> [pkcs1|oaep]\n       \
+                 decrypt <private key file> <input file> [output file] [pkcs1|oaep]\n       \
+                 sign <private key file> <input file or text> <signature file>\n       \
+                 verify <public key file> <input file or text> <signature file>";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    let mut next = || args.next().expect(usage);
+    match command.as_str() {
+        "keygen" => Command::Keygen(next().parse().expect("Can't parse key size"), next(), next()),
+        "encrypt" => Command::Encrypt(next(), next(), next(), parse_padding(args.next())),
+        "decrypt" => {
+            let (key, input) = (next(), next());
+            Command::Decrypt(key, input, args.next(), parse_padding(args.next()))
+        },
+        "sign" => Command::Sign(next(), next(), next()),
+        "verify" => Command::Verify(next(), next(), next()),
+        _ => panic!("{}", usage),
+    }
+}
+
+fn read_key(path: &str) -> String {
+    std::fs::read_to_string(path).expect("Can't read key file")
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Keygen(bits, private_file, public_file) => {
+            let key = rsa::generate(bits)?;
+            std::fs::write(private_file, key.to_pem()).expect("Can't write private key file");
+            std::fs::write(public_file, key.public_key().to_pem()).expect("Can't write public key file");
+            println!("Generated {}-bit key", key.n.bits());
+        },
+        Command::Encrypt(key_file, input, output_file, padding) => {
+            let key = rsa::PublicKey::from_pem(&read_key(&key_file))?;
+            let data = std::fs::read(&input).unwrap_or(input.into_bytes());
+            let output = match padding {
+                Padding::Oaep => rsa::oaep::encrypt(&key, &data, b"")?,
+                Padding::Pkcs1 => rsa::pkcs1v15::encrypt(&key, &data)?,
+            };
+            std::fs::write(output_file, output).expect("Can't write output file");
+        },
+        Command::Decrypt(key_file, input, output_file, padding) => {
+            let key = rsa::PrivateKey::from_pem(&read_key(&key_file))?;
+            let data = std::fs::read(&input).expect("Can't read input file");
+            let output = match padding {
+                Padding::Oaep => rsa::oaep::decrypt(&key, &data, b"")?,
+                Padding::Pkcs1 => rsa::pkcs1v15::decrypt(&key, &data)?,
+            };
+            match output_file {
+                Some(filename) => { std::fs::write(filename, &output).expect("Can't write output file"); },
+                None => println!("{}", String::from_utf8_lossy(&output)),
+            }
+        },
+        Command::Sign(key_file, input, signature_file) => {
+            let key = rsa::PrivateKey::from_pem(&read_key(&key_file))?;
+            let data = std::fs::read(&input).unwrap_or(input.into_bytes());
+            std::fs::write(signature_file, rsa::pss::sign(&key, &data)?).expect("Can't write signature file");
+        },
+        Command::Verify(key_file, input, signature_file) => {
+            let key = rsa::PublicKey::from_pem(&read_key(&key_file))?;
+            let data = std::fs::read(&input).unwrap_or(input.into_bytes());
+            let signature = std::fs::read(signature_file).expect("Can't read signature file");
+            rsa::pss::verify(&key, &data, &signature)?;
+            println!("Signature is valid");
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,76 @@
+use security::*;
+use security::key_exchange::{self, Scheme};
+
+enum Command {
+    Keygen(Scheme, String, String),
+    Apply(OperationMode, String, String, String, String, Option<String>),
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: keygen <x25519|modp2048|modp3072|modp4096> <private key file> <public key file>\n       \
+                 <operation mode> <own private key file> <peer public key file> <cipher: xor|aes-ecb|aes-cbc|aes-ctr> \
+                 <input file or text> [output file]";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    let mut next = || args.next().expect(usage);
+    if command == "keygen" {
+        Command::Keygen(next().parse().unwrap_or_else(|e: AlgorithmError| panic!("{}", e.what)), next(), next())
+    } else {
+        let mode = command.parse().expect("Can't parse OperationMode");
+        let (private, public, cipher, input) = (next(), next(), next(), next());
+        Command::Apply(mode, private, public, cipher, input, args.next())
+    }
+}
+
+/// Key files hold one line: scheme name and hex key
+fn write_key(path: &str, scheme: Scheme, key: &[u8]) {
+    std::fs::write(path, format!("{} {}\n", scheme, utils::to_hex(key))).expect("Can't write key file");
+}
+
+fn read_key(path: &str) -> Result<(Scheme, Vec<u8>), AlgorithmError> {
+    let contents = std::fs::read_to_string(path).expect("Can't read key file");
+    match contents.trim().split_once(' ') {
+        Some((scheme, key)) => Ok((scheme.parse()?, utils::from_hex(key).map_err(|what| AlgorithmError { what })?)),
+        None => Err(AlgorithmError { what: format!("Malformed key file {}", path) }),
+    }
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Keygen(scheme, private_file, public_file) => {
+            let (private, public) = scheme.generate()?;
+            write_key(&private_file, scheme, &private);
+            write_key(&public_file, scheme, &public);
+            println!("Generated {} key pair", scheme);
+        },
+        Command::Apply(mode, private_file, public_file, cipher_name, input, output_file) => {
+            let (scheme, private) = read_key(&private_file)?;
+            let (peer_scheme, peer_public) = read_key(&public_file)?;
+            if scheme != peer_scheme {
+                return Err(AlgorithmError { what: format!("Key schemes differ: {} and {}", scheme, peer_scheme) });
+            }
+
+            let shared = scheme.shared_secret(&private, &peer_public)?;
+            let cipher = key_exchange::session_cipher(&shared, &cipher_name)?;
+
+            let mut data = std::fs::read(&input).unwrap_or(input.into_bytes());
+            match mode {
+                OperationMode::Encrypt => cipher.encrypt(&mut data)?,
+                OperationMode::Decrypt => cipher.decrypt(&mut data)?,
+            };
+            match output_file {
+                Some(filename) => { std::fs::write(filename, &data).expect("Can't write output file"); },
+                None => println!("{}", String::from_utf8_lossy(&data)),
+            }
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,66 @@
+use security::*;
+
+enum Command {
+    Split(u8, u8, String, String),
+    Combine(Vec<String>, Option<String>),
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: split <threshold> <number of shares> <input file or text> <share file prefix>\n       \
+                 combine <share file>... [-o <output file>]";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    match command.as_str() {
+        "split" => Command::Split(
+            args.next().expect(usage).parse().expect("Can't parse threshold"),
+            args.next().expect(usage).parse().expect("Can't parse number of shares"),
+            args.next().expect(usage),
+            args.next().expect(usage),
+        ),
+        "combine" => {
+            let (mut files, mut output) = (Vec::new(), None);
+            while let Some(arg) = args.next() {
+                if arg == "-o" {
+                    output = Some(args.next().expect(usage));
+                } else {
+                    files.push(arg);
+                }
+            }
+            Command::Combine(files, output)
+        },
+        _ => panic!("{}", usage),
+    }
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Split(threshold, count, input, prefix) => {
+            let secret = std::fs::read(&input).unwrap_or(input.into_bytes());
+            for share in shamir::split(&secret, threshold, count)? {
+                let filename = format!("{}.{}", prefix, share.index);
+                std::fs::write(&filename, format!("{}\n", share)).expect("Can't write share file");
+                println!("{}", filename);
+            }
+        },
+        Command::Combine(files, output_file) => {
+            let shares = files.iter()
+                .map(|f| std::fs::read_to_string(f).expect("Can't read share file").parse::<shamir::Share>()
+                    .map_err(|e| AlgorithmError { what: format!("{}: {}", f, e.what) }))
+                .collect::<Result<Vec<_>, _>>()?;
+            let secret = shamir::combine(&shares)?;
+            match output_file {
+                Some(filename) => { std::fs::write(filename, &secret).expect("Can't write output file"); },
+                None => println!("{}", String::from_utf8_lossy(&secret)),
+            }
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
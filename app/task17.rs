@@ -0,0 +1,67 @@
+use security::*;
+use security::hotp::{self, Kind, OtpAuthUri};
+
+enum Command {
+    Code(String, Option<u64>),
+    Verify(String, String, u64, Option<u64>),
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: code <otpauth:// URI or Base32 secret> [unix time]\n       \
+                 verify <otpauth:// URI or Base32 secret> <code> [window in steps=1] [unix time]";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    let parse_time = |t: String| t.parse().expect("Can't parse unix time");
+    match command.as_str() {
+        "code" => Command::Code(args.next().expect(usage), args.next().map(parse_time)),
+        "verify" => Command::Verify(
+            args.next().expect(usage),
+            args.next().expect(usage),
+            args.next().map_or(1, |w| w.parse().expect("Can't parse window")),
+            args.next().map(parse_time),
+        ),
+        _ => panic!("{}", usage),
+    }
+}
+
+/// Bare secret means TOTP with default parameters, like authenticator apps assume
+fn parse_key(key: &str) -> Result<Kind, AlgorithmError> {
+    if key.starts_with("otpauth://") {
+        return Ok(key.parse::<OtpAuthUri>()?.kind);
+    }
+    let generator = hotp::Hotp::new(&codec::base32::decode(key, codec::Strictness::Lenient)?, hotp::Algorithm::Sha1, hotp::DEFAULT_DIGITS)?;
+    Ok(Kind::Totp(hotp::Totp::new(generator, hotp::DEFAULT_PERIOD)?))
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Code(key, time) => {
+            let code = match parse_key(&key)? {
+                Kind::Hotp { generator, counter } => generator.generate(counter),
+                Kind::Totp(totp) => totp.generate(time.unwrap_or_else(hotp::now)),
+            };
+            println!("{}", code);
+        },
+        Command::Verify(key, code, window, time) => {
+            match parse_key(&key)? {
+                Kind::Hotp { generator, counter } => match generator.verify(&code, counter, window) {
+                    Some(matched) => println!("Valid for counter {}, next counter is {}", matched, matched + 1),
+                    None => return Err(AlgorithmError { what: "Invalid code".to_string() }),
+                },
+                Kind::Totp(totp) => match totp.verify(&code, time.unwrap_or_else(hotp::now), window) {
+                    Some(offset) => println!("Valid, {} time steps off", offset),
+                    None => return Err(AlgorithmError { what: "Invalid code".to_string() }),
+                },
+            }
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
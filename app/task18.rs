@@ -0,0 +1,86 @@
+use security::*;
+use security::padding_oracle::{self, LocalOracle, Oracle, TcpOracle};
+
+use std::io::Write;
+use std::net::TcpListener;
+
+enum Command {
+    Serve(String, Vec<u8>),
+    Encrypt(Vec<u8>, String),
+    Attack(String, Vec<u8>),
+    Demo(String),
+}
+
+fn parse_hex(s: &str) -> Vec<u8> {
+    utils::from_hex(s).unwrap_or_else(|e| panic!("Can't parse hex: {}", e))
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: serve <address:port> <hex key>\n       \
+                 encrypt <hex key> <plaintext>\n       \
+                 attack <address:port> <hex of IV and ciphertext>\n       \
+                 demo <plaintext>";
+    let mut args = std::env::args().skip(1);
+
+    let command = args.next().expect(usage);
+    let mut next = || args.next().expect(usage);
+    match command.as_str() {
+        "serve" => Command::Serve(next(), parse_hex(&next())),
+        "encrypt" => Command::Encrypt(parse_hex(&next()), next()),
+        "attack" => Command::Attack(next(), parse_hex(&next())),
+        "demo" => Command::Demo(next()),
+        _ => panic!("{}", usage),
+    }
+}
+
+fn run_attack(oracle: &mut dyn Oracle, data: &[u8]) -> Result<(), AlgorithmError> {
+    if data.len() < aes::BLOCK_SIZE {
+        return Err(AlgorithmError { what: "Input is shorter than IV".to_string() });
+    }
+    let mut iv = [0u8; aes::BLOCK_SIZE];
+    iv.copy_from_slice(&data[..aes::BLOCK_SIZE]);
+
+    let recovered = padding_oracle::attack(oracle, &iv, &data[aes::BLOCK_SIZE..], |p| {
+        eprint!("\rblock {}/{}, byte {:2}/{}, {} queries",
+                p.block + 1, p.blocks, p.recovered, aes::BLOCK_SIZE, p.queries);
+        std::io::stderr().flush().unwrap();
+    })?;
+    eprintln!();
+
+    println!("{}", String::from_utf8_lossy(&recovered.plaintext));
+    println!("Oracle queries: {} ({:.1} per byte)",
+             recovered.queries, recovered.queries as f64 / (data.len() - aes::BLOCK_SIZE) as f64);
+    Ok(())
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Serve(address, key) => {
+            let mut oracle = LocalOracle::new(&key)?;
+            let listener = TcpListener::bind(&address).expect("Can't bind address");
+            eprintln!("Padding oracle listening on {}", listener.local_addr().unwrap());
+            padding_oracle::serve(listener, &mut oracle).expect("Oracle service failed");
+        },
+        Command::Encrypt(key, plaintext) => {
+            let (iv, ciphertext) = LocalOracle::new(&key)?.encrypt(plaintext.as_bytes())?;
+            println!("{}{}", utils::to_hex(&iv), utils::to_hex(&ciphertext));
+        },
+        Command::Attack(address, data) => {
+            let mut oracle = TcpOracle::connect(&address).expect("Can't connect to oracle");
+            run_attack(&mut oracle, &data)?;
+        },
+        Command::Demo(plaintext) => {
+            let mut oracle = LocalOracle::with_random_key()?;
+            let (iv, ciphertext) = oracle.encrypt(plaintext.as_bytes())?;
+            run_attack(&mut oracle, &[&iv[..], &ciphertext].concat())?;
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,107 @@
+use security::*;
+use security::utils::{take_option, write_output};
+
+enum Command {
+    Hide(String, String, String, Option<String>, Option<String>),
+    Reveal(String, String, Option<String>, Option<String>),
+    Capacity(String, Vec<String>),
+    Schemes,
+}
+
+fn parse_args() -> Command {
+    let usage = "USAGE: hide <scheme[:parameter]> <container file> <payload file or text> [output file]\n       \
+                 reveal <scheme[:parameter]> <container file> [output file]\n       \
+                 capacity <container file> [scheme[:parameter]...]\n       \
+                 schemes\n       \
+                 hide and reveal accept --passphrase <passphrase> to encrypt the hidden payload";
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let passphrase = take_option(&mut args, "--passphrase");
+    let mut args = args.into_iter();
+
+    let command = args.next().expect(usage);
+    let mut next = || args.next().expect(usage);
+    match command.as_str() {
+        "hide" => {
+            let (scheme, container, input) = (next(), next(), next());
+            Command::Hide(scheme, container, input, args.next(), passphrase)
+        },
+        "reveal" => {
+            let (scheme, container) = (next(), next());
+            Command::Reveal(scheme, container, args.next(), passphrase)
+        },
+        "capacity" => {
+            let container = next();
+            Command::Capacity(container, args.collect())
+        },
+        "schemes" => Command::Schemes,
+        _ => panic!("{}", usage),
+    }
+}
+
+/// Formats 1-based numbers of lines with non-zero capacity as ranges: "1-3, 5"
+fn contributing_lines(capacities: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in capacities.iter().enumerate().filter(|(_, &c)| c > 0) {
+        match ranges.last_mut() {
+            Some((_, end)) if *end == i => *end = i + 1,
+            _ => ranges.push((i + 1, i + 1)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn run(command: Command) -> Result<(), AlgorithmError> {
+    match command {
+        Command::Hide(scheme, container_file, input, output_file, passphrase) => {
+            let carrier = carrier_by_name(&scheme)?;
+            let container = read_container(&container_file)?;
+            let payload = std::fs::read(&input).unwrap_or_else(|_| input.into_bytes());
+            let hidden = hide_payload(&*carrier, &container, &payload, passphrase.as_deref())?;
+            write_output(hidden.as_bytes(), output_file);
+        },
+        Command::Reveal(scheme, container_file, output_file, passphrase) => {
+            let carrier = carrier_by_name(&scheme)?;
+            let container = read_container(&container_file)?;
+            let payload = reveal_payload(&*carrier, &container, passphrase.as_deref())?;
+            write_output(&payload, output_file);
+        },
+        Command::Capacity(container_file, schemes) => {
+            let container = read_container(&container_file)?;
+            let schemes = if schemes.is_empty() {
+                CARRIER_NAMES.iter().map(|s| s.to_string()).collect()
+            } else {
+                schemes
+            };
+
+            for scheme in schemes {
+                let carrier = carrier_by_name(&scheme)?;
+                let by_line = carrier.capacity_by_line(&container);
+                let used = by_line.iter().filter(|&&c| c > 0).count();
+                let sealed = carrier.payload_capacity(&container)
+                    .saturating_sub(container::HEADER_SIZE + container::TAG_SIZE);
+
+                println!("{}: {} bits, {} bytes of text ({} with --passphrase)",
+                         scheme, carrier.capacity(&container), carrier.payload_capacity(&container), sealed);
+                println!("  {} of {} lines contribute: {}", used, by_line.len(), contributing_lines(&by_line));
+            }
+        },
+        Command::Schemes => {
+            for name in CARRIER_NAMES.iter() {
+                let metadata = carrier_by_name(name)?.metadata();
+                println!("{:12} {}", metadata.name, metadata.description);
+            }
+        },
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(AlgorithmError { what }) = run(parse_args()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
+}
@@ -1,25 +1,58 @@
 use security::*;
+use security::cipher::{Cipher, Xor};
+use security::codec::{Encoding, Strictness};
+use security::utils::take_option;
 
-struct Args(String, String, Option<String>);
+struct Args {
+    input: String,
+    key: String,
+    output: Option<String>,
+    in_encoding: Encoding,
+    out_encoding: Encoding,
+    key_encoding: Encoding,
+}
 
 fn parse_args() -> Args {
-    let usage = "USAGE: <input file or text> <key> [output file]";
-    let mut args = std::env::args().into_iter().skip(1);
-
-    Args(
-        args.next().expect(usage),
-        args.next().expect(usage),
-        args.next(),
-    )
+    let usage = "USAGE: <input file or text> <key> [output file] \
+                 [--in-encoding <encoding>] [--out-encoding <encoding>] [--key-encoding <encoding>]\n       \
+                 encodings: raw (default), hex, base64, base64-nopad, base64url, base64url-nopad, base32, ascii85\n       \
+                 output is plain XOR without integrity protection, use task9 for an authenticated container";
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let mut encoding = |name: &str| take_option(&mut args, name)
+        .map_or(Encoding::Raw, |e| e.parse().unwrap_or_else(|e: AlgorithmError| panic!("{}", e.what)));
+    let (in_encoding, out_encoding, key_encoding) =
+        (encoding("--in-encoding"), encoding("--out-encoding"), encoding("--key-encoding"));
+
+    let mut args = args.into_iter();
+    Args {
+        input: args.next().expect(usage),
+        key: args.next().expect(usage),
+        output: args.next(),
+        in_encoding, out_encoding, key_encoding,
+    }
 }
 
-fn main() {
-    let Args(input, key, output) = parse_args();
+fn run(args: Args) -> Result<Vec<u8>, AlgorithmError> {
+    let input = std::fs::read(&args.input).unwrap_or(args.input.into_bytes());
+    let mut text = args.in_encoding.decode(&input, Strictness::Lenient)?;
+    let key = args.key_encoding.decode(args.key.as_bytes(), Strictness::Lenient)?;
+
+    Xor::new(&key)?.encrypt(&mut text)?;
+    Ok(args.out_encoding.encode(&text))
+}
 
-    let mut text = std::fs::read(&input)
-        .unwrap_or(input.into_bytes());
+fn main() {
+    let args = parse_args();
+    let output = args.output.clone();
 
-    xor_inplace(&mut text, key.as_bytes());
+    let text = match run(args) {
+        Ok(text) => text,
+        Err(AlgorithmError { what }) => {
+            eprintln!("AlgorithmError: {}", what);
+            return;
+        },
+    };
 
     if let Some(filename) = output {
         std::fs::write(filename, &text).expect("Can't write output file");
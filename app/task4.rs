@@ -1,41 +1,41 @@
 use security::*;
+use security::utils::take_option;
 
-struct Args(OperationMode, String, String, char, Option<String>);
+struct Args(OperationMode, String, String, Box<dyn Carrier>, Option<String>, Option<String>);
 
 fn parse_args() -> Args {
-    let usage = "USAGE: <operation mode> <container file> <input file or text> [secret char] [output file]";
-    let mut args = std::env::args().into_iter().skip(1);
+    let usage = "USAGE: <operation mode> <container file> <input file or text> [secret char] [output file] [--passphrase <passphrase>] \
+                 [--snow <bits per group>[,<groups per line>]]\n       \
+                 with a passphrase the text is encrypted before hiding and decrypted after revealing\n       \
+                 with --snow several bits per line are stored in trailing spaces and tabs instead of a secret char, which is then ignored";
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let passphrase = take_option(&mut args, "--passphrase");
+    let snow = take_option(&mut args, "--snow").map(|options| {
+        let options = options.split(',').map(|o| o.parse().expect("Can't parse snow options")).collect::<Vec<usize>>();
+        lines::Lines::snow(options[0], options.get(1).copied().unwrap_or(lines::DEFAULT_GROUPS_PER_LINE))
+            .unwrap_or_else(|e| panic!("{}", e.what))
+    });
+    let mut args = args.into_iter();
+
+    let mode = args.next().expect(usage).parse().expect("Can't parse OperationMode");
+    let (container_file, input) = (args.next().expect(usage), args.next().expect(usage));
+    let secret_char = args.next().map_or(' ', |s| s.chars().next().unwrap());
 
     Args(
-        args.next().expect(usage).parse().expect("Can't parse OperationMode"),
-        args.next().expect(usage),
-        args.next().expect(usage),
-        args.next().map_or(' ', |s| s.chars().next().unwrap()),
+        mode,
+        container_file,
+        input,
+        Box::new(snow.unwrap_or_else(|| lines::Lines::new(secret_char))),
         args.next(),
+        passphrase,
     )
 }
 
 fn main() {
-    let Args(mode, container_file, input, secret_char, output_file) = parse_args();
-
-    let mut container = std::fs::read_to_string(container_file)
-        .expect("Cannot read container")
-        .split_terminator('\n')
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>();
-
-    let text = std::fs::read_to_string(&input).unwrap_or(input);
-
-    match
-        match mode {
-            OperationMode::Encrypt => lines::hide(&text, &mut container, secret_char),
-            OperationMode::Decrypt => lines::reveal(&container, secret_char),
-        }
-        {
-            Ok(output) => match output_file {
-                Some(filename) => { std::fs::write(filename, output.as_bytes()).expect("Can't write output file"); },
-                None => println!("{}", output)
-            },
-            Err(AlgorithmError { what }) => eprintln!("AlgorithmError: {}", what),
-        };
+    let Args(mode, container_file, input, carrier, output_file, passphrase) = parse_args();
+    if let Err(AlgorithmError { what }) =
+        hide_or_reveal(&*carrier, &mode, &container_file, &input, output_file, passphrase.as_deref()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
 }
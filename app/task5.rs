@@ -1,37 +1,31 @@
 use security::*;
-use security::ru_en_similarity::MappingDirection;
+use security::utils::take_option;
+use security::ru_en_similarity::{HomoglyphTable, Homoglyphs, MappingDirection};
 
-struct Args(OperationMode, String, String, MappingDirection, Option<String>);
+struct Args(OperationMode, String, String, Box<dyn Carrier>, Option<String>, Option<String>);
 
 fn parse_args() -> Args {
-    let usage = "USAGE: <operation mode> <container file> <input file or text> [direction] [output file]";
-    let mut args = std::env::args().into_iter().skip(1);
+    let usage = "USAGE: <operation mode> <container file> <input file or text> [direction] [output file] [--passphrase <passphrase>] [--table <table>]\n       \
+                 with a passphrase the text is encrypted before hiding and decrypted after revealing\n       \
+                 table is basic (default), cyrillic, greek, latin1, extended or a table file";
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let passphrase = take_option(&mut args, "--passphrase");
+    let table = take_option(&mut args, "--table").map_or_else(HomoglyphTable::default, |t|
+        HomoglyphTable::load(&t).unwrap_or_else(|e| panic!("{}", e.what)));
+    let mut args = args.into_iter();
 
-    Args(
-        args.next().expect(usage).parse().expect("Can't parse OperationMode"),
-        args.next().expect(usage),
-        args.next().expect(usage),
-        args.next().map_or(MappingDirection::EnRu, |s| s.parse().expect("Can't parse direction")),
-        args.next(),
-    )
+    let mode = args.next().expect(usage).parse().expect("Can't parse OperationMode");
+    let (container_file, input) = (args.next().expect(usage), args.next().expect(usage));
+    let direction = args.next().map_or(MappingDirection::EnRu, |s| s.parse().expect("Can't parse direction"));
+
+    Args(mode, container_file, input, Box::new(Homoglyphs { direction, table }), args.next(), passphrase)
 }
 
 fn main() {
-    let Args(mode, container_file, input, direction, output_file) = parse_args();
-
-    let container = std::fs::read_to_string(container_file).expect("Cannot read container");
-    let text = std::fs::read_to_string(&input).unwrap_or(input);
-
-    match
-        match mode {
-            OperationMode::Encrypt => ru_en_similarity::hide(&text, &container, direction),
-            OperationMode::Decrypt => ru_en_similarity::reveal(&container, direction),
-        }
-        {
-            Ok(output) => match output_file {
-                Some(filename) => { std::fs::write(filename, output.as_bytes()).expect("Can't write output file"); },
-                None => println!("{}", output)
-            },
-            Err(AlgorithmError { what }) => eprintln!("AlgorithmError: {}", what),
-        };
+    let Args(mode, container_file, input, carrier, output_file, passphrase) = parse_args();
+    if let Err(AlgorithmError { what }) =
+        hide_or_reveal(&*carrier, &mode, &container_file, &input, output_file, passphrase.as_deref()) {
+        eprintln!("AlgorithmError: {}", what);
+        std::process::exit(1);
+    }
 }
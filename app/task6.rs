@@ -1,31 +1,53 @@
 use security::checksum;
+use security::codec::{Encoding, Strictness};
+use security::AlgorithmError;
+use security::utils::take_option;
 
 use std::path::PathBuf;
 
-struct Args(String, PathBuf);
+struct Args(String, PathBuf, Encoding, Encoding);
 
 fn parse_args() -> Args {
-    let usage = "USAGE: <algorithm name> <file path>";
+    let usage = "USAGE: <algorithm name> <file path> [--in-encoding <encoding>] [--out-encoding <encoding>]\n       \
+                 encodings: raw, hex, base64, base64-nopad, base64url, base64url-nopad, base32, ascii85; \
+                 input is raw and output is hex by default";
 
-    let mut args = std::env::args().into_iter().skip(1);
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut encoding = |name: &str, default: Encoding| take_option(&mut args, name)
+        .map_or(default, |e| e.parse().unwrap_or_else(|e: AlgorithmError| panic!("{}", e.what)));
+    let (in_encoding, out_encoding) = (encoding("--in-encoding", Encoding::Raw), encoding("--out-encoding", Encoding::Hex));
 
+    let mut args = args.into_iter();
     Args(
         args.next().expect(usage),
         args.next().expect(usage).into(),
+        in_encoding,
+        out_encoding,
     )
 }
 
-fn checksum(algo: &str, data: Vec<u8>) -> String {
+fn checksum(algo: &str, data: &[u8]) -> Option<Vec<u8>> {
     match algo.to_lowercase().as_str() {
-        "xor" => checksum::parity_bit(data),
-        "sha256" => checksum::sha::sha256(data),
-        _ => "unknown algorithm".to_string(),
+        "xor" => Some(vec![checksum::parity_byte(data)]),
+        "sha256" => Some(checksum::sha::sha256_digest(data).to_vec()),
+        _ => None,
     }
 }
 
 fn main() {
-    let Args(algo, path) = parse_args();
+    let Args(algo, path, in_encoding, out_encoding) = parse_args();
 
     let data = std::fs::read(path).expect("Can't open file!");
-    println!("{} = {}", algo, checksum(&algo, data));
+    let data = match in_encoding.decode(&data, Strictness::Lenient) {
+        Ok(data) => data,
+        Err(AlgorithmError { what }) => {
+            eprintln!("AlgorithmError: {}", what);
+            return;
+        },
+    };
+
+    match checksum(&algo, &data) {
+        Some(digest) => println!("{} = {}", algo, String::from_utf8_lossy(&out_encoding.encode(&digest))),
+        None => println!("{} = unknown algorithm", algo),
+    }
 }
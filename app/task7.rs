@@ -1,5 +1,3 @@
-#![feature(duration_float)]
-
 use security::{sig_search, utils};
 
 use std::path::PathBuf;
@@ -11,7 +9,7 @@ struct Args(PathBuf, String);
 fn parse_args() -> Args {
     let usage = "USAGE: <directory> <signature file or string to search for>";
 
-    let mut args = std::env::args().into_iter().skip(1);
+    let mut args = std::env::args().skip(1);
 
     Args(
         args.next().expect(usage).into(),
@@ -31,9 +29,9 @@ fn main() {
 
     let t = Instant::now();
     let mut total_size = 0;
-    for (_i, file) in all_files.iter().rev().enumerate() {
-        if let Some(res) = sig_search::search_file(&file, &signature)
-            .expect(format!("Error searching file: {:?}", file).as_str())
+    for file in all_files.iter().rev() {
+        if let Some(res) = sig_search::search_file(file, &signature)
+            .unwrap_or_else(|_| panic!("Error searching file: {:?}", file))
         {
             println!("{:?}: found at byte position {}", res.path, res.start);
         }
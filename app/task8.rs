@@ -0,0 +1,39 @@
+use security::*;
+
+struct Args(OperationMode, String, String, String, Option<String>);
+
+fn parse_args() -> Args {
+    let usage = format!(
+        "USAGE: <operation mode> <cipher: {}> <hex key> <input file or text> [output file]",
+        cipher::CIPHER_NAMES.join(", ")
+    );
+    let mut args = std::env::args().skip(1);
+
+    Args(
+        args.next().expect(&usage).parse().expect("Can't parse OperationMode"),
+        args.next().expect(&usage),
+        args.next().expect(&usage),
+        args.next().expect(&usage),
+        args.next(),
+    )
+}
+
+fn main() {
+    let Args(mode, cipher_name, key, input, output_file) = parse_args();
+
+    let key = utils::from_hex(&key).expect("Can't parse key");
+    let mut data = std::fs::read(&input).unwrap_or(input.into_bytes());
+
+    let result = cipher::by_name(&cipher_name, &key).and_then(|cipher| match mode {
+        OperationMode::Encrypt => cipher.encrypt(&mut data),
+        OperationMode::Decrypt => cipher.decrypt(&mut data),
+    });
+
+    match result {
+        Ok(()) => match output_file {
+            Some(filename) => { std::fs::write(filename, &data).expect("Can't write output file"); },
+            None => println!("{}", String::from_utf8_lossy(&data)),
+        },
+        Err(AlgorithmError { what }) => eprintln!("AlgorithmError: {}", what),
+    };
+}
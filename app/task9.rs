@@ -0,0 +1,38 @@
+use security::*;
+use security::container::{self, KdfParams};
+
+struct Args(OperationMode, String, String, Option<String>);
+
+fn parse_args() -> Args {
+    let usage = "USAGE: <operation mode> <input file or text> <passphrase> [output file]";
+    let mut args = std::env::args().skip(1);
+
+    Args(
+        args.next().expect(usage).parse().expect("Can't parse OperationMode"),
+        args.next().expect(usage),
+        args.next().expect(usage),
+        args.next(),
+    )
+}
+
+fn main() {
+    let Args(mode, input, passphrase, output_file) = parse_args();
+
+    let data = std::fs::read(&input).unwrap_or(input.into_bytes());
+
+    match
+        match mode {
+            OperationMode::Encrypt => container::seal(passphrase.as_bytes(), &data, &KdfParams::default()),
+            OperationMode::Decrypt => container::open(passphrase.as_bytes(), &data),
+        }
+        {
+            Ok(output) => match output_file {
+                Some(filename) => { std::fs::write(filename, &output).expect("Can't write output file"); },
+                None => println!("{}", String::from_utf8_lossy(&output)),
+            },
+            Err(AlgorithmError { what }) => {
+                eprintln!("AlgorithmError: {}", what);
+                std::process::exit(1);
+            },
+        };
+}
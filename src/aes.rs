@@ -0,0 +1,466 @@
+use std::convert::TryInto;
+
+use crate::utils::AlgorithmError;
+
+pub const BLOCK_SIZE: usize = 16;
+
+pub type Block = [u8; BLOCK_SIZE];
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Multiplication by x (i.e. {02}) in GF(2^8)
+#[inline]
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0x00 }
+}
+
+/// Multiplication in GF(2^8) modulo AES polynomial x^8 + x^4 + x^3 + x + 1
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut result = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            result ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    result
+}
+
+/// AES block cipher with an expanded key. Key length (16, 24 or 32 bytes) selects
+/// AES-128, AES-192 or AES-256 respectively.
+///
+/// Implemented straight from FIPS-197, without any lookup-table tricks, so this is
+/// neither fast nor constant-time:
+/// https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf
+pub struct Aes {
+    round_keys: Vec<Block>,
+}
+
+impl Aes {
+
+    pub fn new(key: &[u8]) -> Result<Aes, AlgorithmError> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            n => return Err(AlgorithmError {
+                what: format!("AES key must be 16, 24 or 32 bytes long, got {} bytes", n)
+            }),
+        };
+        let rounds = nk + 6;
+
+        // key schedule, word by word (section 5.2)
+        let mut words: Vec<[u8; 4]> = key.chunks(4).map(|w| w.try_into().unwrap()).collect();
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+                temp[0] ^= RCON[i / nk - 1];
+            } else if nk > 6 && i % nk == 4 {
+                temp.iter_mut().for_each(|b| *b = SBOX[*b as usize]);
+            }
+            let prev = words[i - nk];
+            words.push([prev[0] ^ temp[0], prev[1] ^ temp[1], prev[2] ^ temp[2], prev[3] ^ temp[3]]);
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|round| {
+                let mut block = [0u8; BLOCK_SIZE];
+                for (i, word) in round.iter().enumerate() {
+                    block[4 * i..4 * i + 4].copy_from_slice(word);
+                }
+                block
+            })
+            .collect();
+
+        Ok(Aes { round_keys })
+    }
+
+    /// Number of rounds: 10, 12 or 14
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+
+    /// Encrypts a single block in place
+    pub fn encrypt_block(&self, block: &mut Block) {
+        let rounds = self.rounds();
+
+        add_round_key(block, &self.round_keys[0]);
+        for round in 1..rounds {
+            sub_bytes(block, &SBOX);
+            shift_rows(block);
+            mix_columns(block);
+            add_round_key(block, &self.round_keys[round]);
+        }
+        sub_bytes(block, &SBOX);
+        shift_rows(block);
+        add_round_key(block, &self.round_keys[rounds]);
+    }
+
+    /// Decrypts a single block in place
+    pub fn decrypt_block(&self, block: &mut Block) {
+        let rounds = self.rounds();
+
+        add_round_key(block, &self.round_keys[rounds]);
+        for round in (1..rounds).rev() {
+            inv_shift_rows(block);
+            sub_bytes(block, &INV_SBOX);
+            add_round_key(block, &self.round_keys[round]);
+            inv_mix_columns(block);
+        }
+        inv_shift_rows(block);
+        sub_bytes(block, &INV_SBOX);
+        add_round_key(block, &self.round_keys[0]);
+    }
+}
+
+// State is stored column by column, so byte (row r, column c) is at index 4 * c + r.
+
+fn add_round_key(state: &mut Block, key: &Block) {
+    state.iter_mut().zip(key.iter()).for_each(|(s, k)| *s ^= k);
+}
+
+fn sub_bytes(state: &mut Block, table: &[u8; 256]) {
+    state.iter_mut().for_each(|b| *b = table[*b as usize]);
+}
+
+fn shift_rows(state: &mut Block) {
+    let old = *state;
+    for r in 1..4 {
+        for c in 0..4 {
+            state[4 * c + r] = old[4 * ((c + r) % 4) + r];
+        }
+    }
+}
+
+fn inv_shift_rows(state: &mut Block) {
+    let old = *state;
+    for r in 1..4 {
+        for c in 0..4 {
+            state[4 * ((c + r) % 4) + r] = old[4 * c + r];
+        }
+    }
+}
+
+fn mix_columns(state: &mut Block) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3]: [u8; 4] = (&*column).try_into().unwrap();
+        column[0] = xtime(a0) ^ (xtime(a1) ^ a1) ^ a2 ^ a3;
+        column[1] = a0 ^ xtime(a1) ^ (xtime(a2) ^ a2) ^ a3;
+        column[2] = a0 ^ a1 ^ xtime(a2) ^ (xtime(a3) ^ a3);
+        column[3] = (xtime(a0) ^ a0) ^ a1 ^ a2 ^ xtime(a3);
+    }
+}
+
+fn inv_mix_columns(state: &mut Block) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3]: [u8; 4] = (&*column).try_into().unwrap();
+        column[0] = gf_mul(a0, 0x0e) ^ gf_mul(a1, 0x0b) ^ gf_mul(a2, 0x0d) ^ gf_mul(a3, 0x09);
+        column[1] = gf_mul(a0, 0x09) ^ gf_mul(a1, 0x0e) ^ gf_mul(a2, 0x0b) ^ gf_mul(a3, 0x0d);
+        column[2] = gf_mul(a0, 0x0d) ^ gf_mul(a1, 0x09) ^ gf_mul(a2, 0x0e) ^ gf_mul(a3, 0x0b);
+        column[3] = gf_mul(a0, 0x0b) ^ gf_mul(a1, 0x0d) ^ gf_mul(a2, 0x09) ^ gf_mul(a3, 0x0e);
+    }
+}
+
+/// Pads data to a multiple of block size according to PKCS#7. Always adds at least one byte.
+pub fn pkcs7_pad(data: &mut Vec<u8>, block_size: usize) {
+    let n = block_size - data.len() % block_size;
+    data.extend(std::iter::repeat_n(n as u8, n));
+}
+
+/// Removes PKCS#7 padding, checking that it is well-formed.
+pub fn pkcs7_unpad(data: &mut Vec<u8>, block_size: usize) -> Result<(), AlgorithmError> {
+    let invalid = |what: &str| Err(AlgorithmError { what: format!("Invalid padding: {}", what) });
+
+    if data.is_empty() || !data.len().is_multiple_of(block_size) {
+        return invalid("data length is not a multiple of block size");
+    }
+
+    let n = *data.last().unwrap() as usize;
+    if n == 0 || n > block_size {
+        return invalid("bad padding length");
+    }
+    if data[data.len() - n..].iter().any(|&b| b as usize != n) {
+        return invalid("bad padding bytes");
+    }
+
+    data.truncate(data.len() - n);
+    Ok(())
+}
+
+fn to_block(chunk: &[u8]) -> Block {
+    chunk.try_into().unwrap()
+}
+
+/// Electronic codebook mode. Do not use it for anything but demonstrating why you shouldn't.
+pub mod ecb {
+    use super::*;
+
+    pub fn encrypt(cipher: &Aes, data: &[u8]) -> Vec<u8> {
+        let mut data = data.to_vec();
+        pkcs7_pad(&mut data, BLOCK_SIZE);
+
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut block = to_block(chunk);
+            cipher.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block);
+        }
+
+        data
+    }
+
+    pub fn decrypt(cipher: &Aes, data: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        if !data.len().is_multiple_of(BLOCK_SIZE) {
+            return Err(AlgorithmError {
+                what: "Ciphertext length is not a multiple of block size".to_string()
+            });
+        }
+
+        let mut data = data.to_vec();
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut block = to_block(chunk);
+            cipher.decrypt_block(&mut block);
+            chunk.copy_from_slice(&block);
+        }
+
+        pkcs7_unpad(&mut data, BLOCK_SIZE)?;
+        Ok(data)
+    }
+}
+
+/// Cipher block chaining mode. IV is not included into the output.
+pub mod cbc {
+    use super::*;
+
+    pub fn encrypt(cipher: &Aes, iv: &Block, data: &[u8]) -> Vec<u8> {
+        let mut data = data.to_vec();
+        pkcs7_pad(&mut data, BLOCK_SIZE);
+
+        let mut prev = *iv;
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut block = to_block(chunk);
+            add_round_key(&mut block, &prev);
+            cipher.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block);
+            prev = block;
+        }
+
+        data
+    }
+
+    pub fn decrypt(cipher: &Aes, iv: &Block, data: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        if !data.len().is_multiple_of(BLOCK_SIZE) {
+            return Err(AlgorithmError {
+                what: "Ciphertext length is not a multiple of block size".to_string()
+            });
+        }
+
+        let mut data = data.to_vec();
+        let mut prev = *iv;
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut block = to_block(chunk);
+            let ciphertext = block;
+            cipher.decrypt_block(&mut block);
+            add_round_key(&mut block, &prev);
+            chunk.copy_from_slice(&block);
+            prev = ciphertext;
+        }
+
+        pkcs7_unpad(&mut data, BLOCK_SIZE)?;
+        Ok(data)
+    }
+}
+
+/// Counter mode. Initial counter block is incremented as a whole 128-bit big-endian number,
+/// as in SP 800-38A. Encryption and decryption are the same operation.
+pub mod ctr {
+    use super::*;
+
+    pub fn apply_keystream(cipher: &Aes, initial_counter: &Block, data: &mut [u8]) {
+        let mut counter = u128::from_be_bytes(*initial_counter);
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut keystream = counter.to_be_bytes();
+            cipher.encrypt_block(&mut keystream);
+            chunk.iter_mut().zip(keystream.iter()).for_each(|(b, k)| *b ^= k);
+            counter = counter.wrapping_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::from_hex;
+
+    fn block(s: &str) -> Block {
+        to_block(&from_hex(s).unwrap())
+    }
+
+    // FIPS-197, appendix C
+    const FIPS_PLAINTEXT: &str = "00112233445566778899aabbccddeeff";
+
+    fn check_fips_vector(key: &str, expected: &str) {
+        let cipher = Aes::new(&from_hex(key).unwrap()).unwrap();
+        let mut data = block(FIPS_PLAINTEXT);
+
+        cipher.encrypt_block(&mut data);
+        assert_eq!(data, block(expected));
+
+        cipher.decrypt_block(&mut data);
+        assert_eq!(data, block(FIPS_PLAINTEXT));
+    }
+
+    #[test]
+    pub fn test_aes128_fips197() {
+        check_fips_vector("000102030405060708090a0b0c0d0e0f", "69c4e0d86a7b0430d8cdb78070b4c55a");
+    }
+
+    #[test]
+    pub fn test_aes192_fips197() {
+        check_fips_vector(
+            "000102030405060708090a0b0c0d0e0f1011121314151617",
+            "dda97ca4864cdfe06eaf70a0ec0d7191",
+        );
+    }
+
+    #[test]
+    pub fn test_aes256_fips197() {
+        check_fips_vector(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            "8ea2b7ca516745bfeafc49904b496089",
+        );
+    }
+
+    #[test]
+    pub fn test_invalid_key_length() {
+        assert!(Aes::new(b"short key").is_err());
+    }
+
+    // SP 800-38A, appendix F
+    const SP_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const SP_PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a\
+                                ae2d8a571e03ac9c9eb76fac45af8e51\
+                                30c81c46a35ce411e5fbc1191a0a52ef\
+                                f69f2445df4f9b17ad2b417be66c3710";
+
+    #[test]
+    pub fn test_ecb_aes128_sp800_38a() {
+        let cipher = Aes::new(&from_hex(SP_KEY).unwrap()).unwrap();
+        let plaintext = from_hex(SP_PLAINTEXT).unwrap();
+
+        let ciphertext = ecb::encrypt(&cipher, &plaintext);
+        assert_eq!(
+            ciphertext[..plaintext.len()].to_vec(),
+            from_hex("3ad77bb40d7a3660a89ecaf32466ef97\
+                      f5d3d58503b9699de785895a96fdbaaf\
+                      43b1cd7f598ece23881b00e3ed030688\
+                      7b0c785e27e8ad3f8223207104725dd4").unwrap()
+        );
+        assert_eq!(ciphertext.len(), plaintext.len() + BLOCK_SIZE);
+        assert_eq!(ecb::decrypt(&cipher, &ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    pub fn test_cbc_aes128_sp800_38a() {
+        let cipher = Aes::new(&from_hex(SP_KEY).unwrap()).unwrap();
+        let iv = block("000102030405060708090a0b0c0d0e0f");
+        let plaintext = from_hex(SP_PLAINTEXT).unwrap();
+
+        let ciphertext = cbc::encrypt(&cipher, &iv, &plaintext);
+        assert_eq!(
+            ciphertext[..plaintext.len()].to_vec(),
+            from_hex("7649abac8119b246cee98e9b12e9197d\
+                      5086cb9b507219ee95db113a917678b2\
+                      73bed6b8e3c1743b7116e69e22229516\
+                      3ff1caa1681fac09120eca307586e1a7").unwrap()
+        );
+        assert_eq!(cbc::decrypt(&cipher, &iv, &ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    pub fn test_ctr_aes128_sp800_38a() {
+        let cipher = Aes::new(&from_hex(SP_KEY).unwrap()).unwrap();
+        let counter = block("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = from_hex(SP_PLAINTEXT).unwrap();
+
+        let mut data = plaintext.clone();
+        ctr::apply_keystream(&cipher, &counter, &mut data);
+        assert_eq!(
+            data,
+            from_hex("874d6191b620e3261bef6864990db6ce\
+                      9806f66b7970fdff8617187bb9fffdff\
+                      5ae4df3edbd5d35e5b4f09020db03eab\
+                      1e031dda2fbe03d1792170a0f3009cee").unwrap()
+        );
+
+        ctr::apply_keystream(&cipher, &counter, &mut data);
+        assert_eq!(data, plaintext);
+    }
+
+    #[test]
+    pub fn test_pkcs7_padding() {
+        let mut data = b"YELLOW SUBMARINE".to_vec();
+        pkcs7_pad(&mut data, 20);
+        assert_eq!(data, b"YELLOW SUBMARINE\x04\x04\x04\x04".to_vec());
+
+        assert!(pkcs7_unpad(&mut data, 20).is_ok());
+        assert_eq!(data, b"YELLOW SUBMARINE".to_vec());
+
+        let mut bad = b"ICE ICE BABY\x01\x02\x03\x04".to_vec();
+        assert!(pkcs7_unpad(&mut bad, BLOCK_SIZE).is_err());
+
+        let mut zero = b"ICE ICE BABY\x00\x00\x00\x00".to_vec();
+        assert!(pkcs7_unpad(&mut zero, BLOCK_SIZE).is_err());
+    }
+
+    #[test]
+    pub fn test_cbc_wrong_key_fails_or_differs() {
+        let iv = [7u8; BLOCK_SIZE];
+        let text = b"attack at dawn".to_vec();
+        let ciphertext = cbc::encrypt(&Aes::new(&[1u8; 16]).unwrap(), &iv, &text);
+
+        match cbc::decrypt(&Aes::new(&[2u8; 16]).unwrap(), &iv, &ciphertext) {
+            Ok(decrypted) => assert_ne!(decrypted, text),
+            Err(AlgorithmError { what }) => assert!(what.starts_with("Invalid padding")),
+        }
+    }
+}
@@ -0,0 +1,912 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+use crate::utils::Xorshift64;
+
+type Limb = u32;
+type DoubleLimb = u64;
+
+const LIMB_BITS: usize = 32;
+
+/// Operands at least this long (in limbs) are multiplied with Karatsuba algorithm
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Arbitrary-precision unsigned integer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    /// Little-endian limbs without leading (most significant) zeros; zero has no limbs at all
+    limbs: Vec<Limb>,
+}
+
+fn normalize(mut limbs: Vec<Limb>) -> Vec<Limb> {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+fn add_slices(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    let (a, b) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0 as DoubleLimb;
+    for (i, &x) in a.iter().enumerate() {
+        let sum = x as DoubleLimb + b.get(i).copied().unwrap_or(0) as DoubleLimb + carry;
+        result.push(sum as Limb);
+        carry = sum >> LIMB_BITS;
+    }
+    if carry != 0 {
+        result.push(carry as Limb);
+    }
+    normalize(result)
+}
+
+/// a - b, a must not be less than b
+fn sub_slices(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0 as DoubleLimb;
+    for (i, &x) in a.iter().enumerate() {
+        let subtrahend = b.get(i).copied().unwrap_or(0) as DoubleLimb + borrow;
+        let minuend = x as DoubleLimb;
+        if minuend >= subtrahend {
+            result.push((minuend - subtrahend) as Limb);
+            borrow = 0;
+        } else {
+            result.push((minuend + (1 << LIMB_BITS) - subtrahend) as Limb);
+            borrow = 1;
+        }
+    }
+    debug_assert_eq!(borrow, 0, "subtraction underflow");
+    normalize(result)
+}
+
+fn cmp_slices(a: &[Limb], b: &[Limb]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn mul_schoolbook(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0 as Limb; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0 as DoubleLimb;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as DoubleLimb * y as DoubleLimb + result[i + j] as DoubleLimb + carry;
+            result[i + j] = t as Limb;
+            carry = t >> LIMB_BITS;
+        }
+        result[i + b.len()] = carry as Limb;
+    }
+    normalize(result)
+}
+
+fn shift_limbs(a: Vec<Limb>, n: usize) -> Vec<Limb> {
+    if a.is_empty() {
+        return a;
+    }
+    let mut result = vec![0; n];
+    result.extend(a);
+    result
+}
+
+/// Karatsuba multiplication: three half-size multiplications instead of four
+fn mul_slices(a: &[Limb], b: &[Limb]) -> Vec<Limb> {
+    if a.len() < KARATSUBA_THRESHOLD || b.len() < KARATSUBA_THRESHOLD {
+        return mul_schoolbook(a, b);
+    }
+
+    let m = a.len().max(b.len()) / 2;
+    let split = |x: &[Limb]| -> (Vec<Limb>, Vec<Limb>) {
+        if x.len() <= m {
+            (normalize(x.to_vec()), Vec::new())
+        } else {
+            (normalize(x[..m].to_vec()), x[m..].to_vec())
+        }
+    };
+    let (a0, a1) = split(a);
+    let (b0, b1) = split(b);
+
+    let z0 = mul_slices(&a0, &b0);
+    let z2 = mul_slices(&a1, &b1);
+    let z1 = mul_slices(&add_slices(&a0, &a1), &add_slices(&b0, &b1));
+    let z1 = sub_slices(&sub_slices(&z1, &z0), &z2);
+
+    add_slices(&add_slices(&z0, &shift_limbs(z1, m)), &shift_limbs(z2, 2 * m))
+}
+
+impl BigUint {
+
+    pub fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn one() -> BigUint {
+        BigUint::from(1u64)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.limbs == [1]
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|l| l & 1 == 0)
+    }
+
+    pub fn is_odd(&self) -> bool {
+        !self.is_even()
+    }
+
+    /// Number of significant bits
+    pub fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * LIMB_BITS - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn bit(&self, i: usize) -> bool {
+        self.limbs.get(i / LIMB_BITS).is_some_and(|l| l >> (i % LIMB_BITS) & 1 == 1)
+    }
+
+    pub fn set_bit(&mut self, i: usize) {
+        if self.limbs.len() <= i / LIMB_BITS {
+            self.limbs.resize(i / LIMB_BITS + 1, 0);
+        }
+        self.limbs[i / LIMB_BITS] |= 1 << (i % LIMB_BITS);
+    }
+
+    /// Number of trailing zero bits; zero for zero
+    pub fn trailing_zeros(&self) -> usize {
+        match self.limbs.iter().position(|&l| l != 0) {
+            Some(i) => i * LIMB_BITS + self.limbs[i].trailing_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0] as u64),
+            2 => Some(self.limbs[0] as u64 | (self.limbs[1] as u64) << LIMB_BITS),
+            _ => None,
+        }
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> BigUint {
+        let limbs = bytes
+            .rchunks(LIMB_BITS / 8)
+            .map(|chunk| chunk.iter().fold(0 as Limb, |acc, &b| acc << 8 | b as Limb))
+            .collect();
+        BigUint { limbs: normalize(limbs) }
+    }
+
+    /// Big-endian bytes without leading zeros (empty for zero)
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let bytes = self.limbs.iter().rev().flat_map(|l| l.to_be_bytes()).collect::<Vec<u8>>();
+        let leading_zeros = bytes.iter().take_while(|&&b| b == 0).count();
+        bytes[leading_zeros..].to_vec()
+    }
+
+    /// Big-endian bytes left-padded with zeros to exactly `len` bytes, if the number fits
+    pub fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_bytes_be();
+        if bytes.len() > len {
+            return None;
+        }
+        let mut result = vec![0u8; len - bytes.len()];
+        result.extend(bytes);
+        Some(result)
+    }
+
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<BigUint, String> {
+        let s = s.trim();
+        if s.is_empty() || !(2..=36).contains(&radix) {
+            return Err(format!("Can't parse number: {:?}", s));
+        }
+        let mut result = BigUint::zero();
+        for c in s.chars().filter(|&c| c != '_') {
+            let digit = c.to_digit(radix).ok_or_else(|| format!("Can't parse number: {:?}", s))?;
+            result = result.mul_small(radix).add_small(digit);
+        }
+        Ok(result)
+    }
+
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        assert!((2..=36).contains(&radix), "unsupported radix: {}", radix);
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut digits = Vec::new();
+        let mut n = self.clone();
+        while !n.is_zero() {
+            let (q, r) = n.div_rem_small(radix);
+            digits.push(std::char::from_digit(r, radix).unwrap());
+            n = q;
+        }
+        digits.iter().rev().collect()
+    }
+
+    fn mul_small(&self, m: Limb) -> BigUint {
+        let mut carry = 0 as DoubleLimb;
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        for &l in self.limbs.iter() {
+            let t = l as DoubleLimb * m as DoubleLimb + carry;
+            limbs.push(t as Limb);
+            carry = t >> LIMB_BITS;
+        }
+        limbs.push(carry as Limb);
+        BigUint { limbs: normalize(limbs) }
+    }
+
+    fn add_small(&self, a: Limb) -> BigUint {
+        BigUint { limbs: add_slices(&self.limbs, &[a]) }
+    }
+
+    fn div_rem_small(&self, d: Limb) -> (BigUint, Limb) {
+        assert!(d != 0, "division by zero");
+        let mut rem = 0 as DoubleLimb;
+        let mut quotient = vec![0 as Limb; self.limbs.len()];
+        for i in (0..self.limbs.len()).rev() {
+            let cur = rem << LIMB_BITS | self.limbs[i] as DoubleLimb;
+            quotient[i] = (cur / d as DoubleLimb) as Limb;
+            rem = cur % d as DoubleLimb;
+        }
+        (BigUint { limbs: normalize(quotient) }, rem as Limb)
+    }
+
+    /// a - b, or None if b > a
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if self < other {
+            None
+        } else {
+            Some(BigUint { limbs: sub_slices(&self.limbs, &other.limbs) })
+        }
+    }
+
+    /// Quotient and remainder (Knuth's algorithm D, TAOCP vol. 2, 4.3.1). Panics on division by zero.
+    pub fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        assert!(!divisor.is_zero(), "division by zero");
+
+        if self < divisor {
+            return (BigUint::zero(), self.clone());
+        }
+        if divisor.limbs.len() == 1 {
+            let (q, r) = self.div_rem_small(divisor.limbs[0]);
+            return (q, BigUint::from(r as u64));
+        }
+
+        // normalize so that the top bit of the divisor is set, which makes quotient digit
+        // estimates off by at most 2
+        let shift = divisor.limbs.last().unwrap().leading_zeros() as usize;
+        let v = (divisor << shift).limbs;
+        let mut u = (self << shift).limbs;
+        u.push(0);
+
+        let n = v.len();
+        let m = u.len() - n - 1;
+        let base: DoubleLimb = 1 << LIMB_BITS;
+        let mut quotient = vec![0 as Limb; m + 1];
+
+        for j in (0..=m).rev() {
+            let numerator = (u[j + n] as DoubleLimb) << LIMB_BITS | u[j + n - 1] as DoubleLimb;
+            let mut qhat = numerator / v[n - 1] as DoubleLimb;
+            let mut rhat = numerator % v[n - 1] as DoubleLimb;
+
+            while qhat >= base || qhat * v[n - 2] as DoubleLimb > (rhat << LIMB_BITS | u[j + n - 2] as DoubleLimb) {
+                qhat -= 1;
+                rhat += v[n - 1] as DoubleLimb;
+                if rhat >= base {
+                    break;
+                }
+            }
+
+            // u[j..=j + n] -= qhat * v
+            let mut borrow = 0i64;
+            for i in 0..n {
+                let p = qhat * v[i] as DoubleLimb;
+                let t = u[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+                u[i + j] = t as Limb;
+                borrow = (p >> LIMB_BITS) as i64 - (t >> LIMB_BITS);
+            }
+            let t = u[j + n] as i64 - borrow;
+            u[j + n] = t as Limb;
+
+            if t < 0 {
+                // estimate was one too large, add divisor back
+                qhat -= 1;
+                let mut carry = 0 as DoubleLimb;
+                for i in 0..n {
+                    let s = u[i + j] as DoubleLimb + v[i] as DoubleLimb + carry;
+                    u[i + j] = s as Limb;
+                    carry = s >> LIMB_BITS;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as Limb);
+            }
+
+            quotient[j] = qhat as Limb;
+        }
+
+        u.truncate(n);
+        let remainder = BigUint { limbs: normalize(u) } >> shift;
+        (BigUint { limbs: normalize(quotient) }, remainder)
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigUint {
+        let mut base = self.clone();
+        let mut result = BigUint::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// self^exp mod modulus, by left-to-right binary exponentiation
+    pub fn modpow(&self, exp: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "modulus is zero");
+        if modulus.is_one() {
+            return BigUint::zero();
+        }
+
+        let base = self % modulus;
+        let mut result = BigUint::one();
+        for i in (0..exp.bits()).rev() {
+            result = &(&result * &result) % modulus;
+            if exp.bit(i) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+        result
+    }
+
+    pub fn gcd(&self, other: &BigUint) -> BigUint {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Inverse of self modulo m (extended Euclidean algorithm), if self and m are coprime
+    pub fn modinv(&self, m: &BigUint) -> Option<BigUint> {
+        if m.is_zero() {
+            return None;
+        }
+
+        let (mut old_r, mut r) = (BigInt::from(self % m), BigInt::from(m.clone()));
+        let (mut old_s, mut s) = (BigInt::from(1i64), BigInt::from(0i64));
+
+        while !r.is_zero() {
+            let q = &old_r / &r;
+            let new_r = &old_r - &(&q * &r);
+            old_r = std::mem::replace(&mut r, new_r);
+            let new_s = &old_s - &(&q * &s);
+            old_s = std::mem::replace(&mut s, new_s);
+        }
+
+        if old_r == BigInt::from(1i64) {
+            Some(old_s.rem_euclid(m))
+        } else {
+            None
+        }
+    }
+
+    /// Uniformly distributed random number in 0..bound. Not suitable for secrets unless
+    /// rng is seeded from a secure source.
+    pub fn random_below(bound: &BigUint, rng: &mut Xorshift64) -> BigUint {
+        assert!(!bound.is_zero(), "empty range");
+        let bits = bound.bits();
+        loop {
+            let limbs = (0..bits.div_ceil(LIMB_BITS)).map(|_| rng.next_u64() as Limb).collect::<Vec<Limb>>();
+            let candidate = BigUint { limbs: normalize(limbs) } & bits;
+            if &candidate < bound {
+                return candidate;
+            }
+        }
+    }
+
+    /// Miller-Rabin probabilistic primality test with `rounds` random bases; composite
+    /// numbers pass with probability at most 4^-rounds.
+    pub fn is_probable_prime(&self, rounds: usize, rng: &mut Xorshift64) -> bool {
+        const SMALL_PRIMES: [u32; 25] = [
+            2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+        ];
+
+        if let Some(n) = self.to_u64().filter(|&n| n < 100) {
+            return SMALL_PRIMES.contains(&(n as u32));
+        }
+        if SMALL_PRIMES.iter().any(|&p| self.div_rem_small(p).1 == 0) {
+            return false;
+        }
+
+        let one = BigUint::one();
+        let n_minus_one = self - &one;
+        let s = n_minus_one.trailing_zeros();
+        let d = &n_minus_one >> s;
+        let two = BigUint::from(2u64);
+        let three = BigUint::from(3u64);
+
+        'witness: for _ in 0..rounds {
+            // a in 2..=n-2
+            let a = &BigUint::random_below(&(self - &three), rng) + &two;
+            let mut x = a.modpow(&d, self);
+            if x == one || x == n_minus_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = &(&x * &x) % self;
+                if x == n_minus_one {
+                    continue 'witness;
+                }
+            }
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Keeps only the lowest `bits` bits
+impl std::ops::BitAnd<usize> for BigUint {
+    type Output = BigUint;
+
+    fn bitand(mut self, bits: usize) -> BigUint {
+        self.limbs.truncate(bits.div_ceil(LIMB_BITS));
+        if !bits.is_multiple_of(LIMB_BITS) {
+            if let Some(top) = self.limbs.get_mut(bits / LIMB_BITS) {
+                *top &= (1 << (bits % LIMB_BITS)) - 1;
+            }
+        }
+        BigUint { limbs: normalize(self.limbs) }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint { limbs: normalize(vec![n as Limb, (n >> LIMB_BITS) as Limb]) }
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_slices(&self.limbs, &other.limbs)
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+
+    /// Decimal, or hexadecimal with 0x prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            Some(hex) => BigUint::from_str_radix(hex, 16),
+            None => BigUint::from_str_radix(s, 10),
+        }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_str_radix(10))
+    }
+}
+
+impl fmt::LowerHex for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_str_radix(16))
+    }
+}
+
+impl Shl<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        if self.is_zero() {
+            return BigUint::zero();
+        }
+        let (limb_shift, bit_shift) = (shift / LIMB_BITS, shift % LIMB_BITS);
+        let mut limbs = vec![0 as Limb; limb_shift];
+        if bit_shift == 0 {
+            limbs.extend_from_slice(&self.limbs);
+        } else {
+            let mut carry = 0 as Limb;
+            for &l in self.limbs.iter() {
+                limbs.push(l << bit_shift | carry);
+                carry = l >> (LIMB_BITS - bit_shift);
+            }
+            limbs.push(carry);
+        }
+        BigUint { limbs: normalize(limbs) }
+    }
+}
+
+impl Shr<usize> for &BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        let (limb_shift, bit_shift) = (shift / LIMB_BITS, shift % LIMB_BITS);
+        if limb_shift >= self.limbs.len() {
+            return BigUint::zero();
+        }
+        let src = &self.limbs[limb_shift..];
+        let limbs = if bit_shift == 0 {
+            src.to_vec()
+        } else {
+            (0..src.len())
+                .map(|i| src[i] >> bit_shift | src.get(i + 1).map_or(0, |&h| h << (LIMB_BITS - bit_shift)))
+                .collect()
+        };
+        BigUint { limbs: normalize(limbs) }
+    }
+}
+
+impl Shl<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        &self << shift
+    }
+}
+
+impl Shr<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shr(self, shift: usize) -> BigUint {
+        &self >> shift
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        BigUint { limbs: add_slices(&self.limbs, &other.limbs) }
+    }
+}
+
+impl Sub<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    /// Panics if other is greater than self; see `checked_sub`
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other).expect("BigUint subtraction underflow")
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        BigUint { limbs: mul_slices(&self.limbs, &other.limbs) }
+    }
+}
+
+impl Div<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).0
+    }
+}
+
+impl Rem<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn rem(self, other: &BigUint) -> BigUint {
+        self.div_rem(other).1
+    }
+}
+
+macro_rules! forward_owned_binop {
+    ($t:ty, $tr:ident, $method:ident) => {
+        impl $tr<$t> for $t {
+            type Output = $t;
+
+            fn $method(self, other: $t) -> $t {
+                (&self).$method(&other)
+            }
+        }
+
+        impl $tr<&$t> for $t {
+            type Output = $t;
+
+            fn $method(self, other: &$t) -> $t {
+                (&self).$method(other)
+            }
+        }
+    };
+}
+
+forward_owned_binop!(BigUint, Add, add);
+forward_owned_binop!(BigUint, Sub, sub);
+forward_owned_binop!(BigUint, Mul, mul);
+forward_owned_binop!(BigUint, Div, div);
+forward_owned_binop!(BigUint, Rem, rem);
+
+/// Arbitrary-precision signed integer, as sign and magnitude.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: BigUint,
+}
+
+impl BigInt {
+
+    fn new(negative: bool, magnitude: BigUint) -> BigInt {
+        // zero is never negative, so that equality works
+        BigInt { negative: negative && !magnitude.is_zero(), magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn magnitude(&self) -> &BigUint {
+        &self.magnitude
+    }
+
+    /// Non-negative remainder of division by m
+    pub fn rem_euclid(&self, m: &BigUint) -> BigUint {
+        let r = &self.magnitude % m;
+        if self.negative && !r.is_zero() { m - &r } else { r }
+    }
+}
+
+impl From<BigUint> for BigInt {
+    fn from(n: BigUint) -> Self {
+        BigInt::new(false, n)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        BigInt::new(n < 0, BigUint::from(n.unsigned_abs()))
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix('-') {
+            Some(rest) => Ok(BigInt::new(true, rest.parse()?)),
+            None => Ok(BigInt::new(false, s.parse()?)),
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude.to_str_radix(10))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude)
+    }
+}
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            BigInt::new(self.negative, &self.magnitude + &other.magnitude)
+        } else if self.magnitude >= other.magnitude {
+            BigInt::new(self.negative, &self.magnitude - &other.magnitude)
+        } else {
+            BigInt::new(other.negative, &other.magnitude - &self.magnitude)
+        }
+    }
+}
+
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative != other.negative, &self.magnitude * &other.magnitude)
+    }
+}
+
+impl Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    /// Truncating division, like for primitive integers
+    fn div(self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative != other.negative, &self.magnitude / &other.magnitude)
+    }
+}
+
+impl Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    /// Remainder has the sign of the dividend, like for primitive integers
+    fn rem(self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative, &self.magnitude % &other.magnitude)
+    }
+}
+
+forward_owned_binop!(BigInt, Add, add);
+forward_owned_binop!(BigInt, Sub, sub);
+forward_owned_binop!(BigInt, Mul, mul);
+forward_owned_binop!(BigInt, Div, div);
+forward_owned_binop!(BigInt, Rem, rem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigUint {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    pub fn test_parse_format() {
+        let n = big("340282366920938463463374607431768211456");
+        assert_eq!(n, &BigUint::one() << 128);
+        assert_eq!(n.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(format!("{:x}", n), "100000000000000000000000000000000");
+        assert_eq!(big("0xDEADbeef"), BigUint::from(0xdeadbeef));
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert!("12a".parse::<BigUint>().is_err());
+
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        assert_eq!(BigUint::from_bytes_be(&bytes).to_bytes_be(), bytes.to_vec());
+        assert_eq!(BigUint::from_bytes_be(&[0, 0, 1]).to_bytes_be_padded(4), Some(vec![0, 0, 0, 1]));
+    }
+
+    #[test]
+    pub fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("987654321098765432109876543210");
+
+        assert_eq!(&a + &b, big("1111111110111111111011111111100"));
+        assert_eq!(&b - &a, big("864197532086419753208641975320"));
+        assert!(a.checked_sub(&b).is_none());
+        assert_eq!(&a * &b, big("121932631137021795226185032733622923332237463801111263526900"));
+        assert_eq!(BigUint::from(2).pow(100), big("1267650600228229401496703205376"));
+
+        let factorial = (1..=30u64).fold(BigUint::one(), |acc, i| acc * BigUint::from(i));
+        assert_eq!(factorial, big("265252859812191058636308480000000"));
+    }
+
+    #[test]
+    pub fn test_division() {
+        let a = big("121932631137021795226185032733622923332237463801111263526901");
+        let b = big("987654321098765432109876543210");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q, big("123456789012345678901234567890"));
+        assert_eq!(r, BigUint::one());
+
+        // divisor which needs "add back" step of algorithm D
+        let a = big("0x7fffffff800000010000000000000000");
+        let b = big("0x800000008000000200000005");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(&(&q * &b) + &r, a);
+        assert!(r < b);
+
+        let mut rng = Xorshift64::new(1);
+        for i in 1..200 {
+            let a = BigUint::random_below(&(&BigUint::one() << (i * 7)), &mut rng);
+            let b = &BigUint::random_below(&(&BigUint::one() << (i * 3)), &mut rng) + &BigUint::one();
+            let (q, r) = a.div_rem(&b);
+            assert_eq!(&(&q * &b) + &r, a);
+            assert!(r < b);
+        }
+    }
+
+    #[test]
+    pub fn test_karatsuba_matches_schoolbook() {
+        let mut rng = Xorshift64::new(2);
+        for &(x, y) in [(40, 40), (100, 70), (33, 200), (257, 256)].iter() {
+            let a = BigUint::random_below(&(&BigUint::one() << (x * LIMB_BITS)), &mut rng);
+            let b = BigUint::random_below(&(&BigUint::one() << (y * LIMB_BITS)), &mut rng);
+            assert_eq!(mul_slices(&a.limbs, &b.limbs), mul_schoolbook(&a.limbs, &b.limbs));
+        }
+    }
+
+    #[test]
+    pub fn test_modular() {
+        assert_eq!(BigUint::from(4).modpow(&BigUint::from(13), &BigUint::from(497)), BigUint::from(445));
+        assert_eq!(BigUint::from(3).modinv(&BigUint::from(11)), Some(BigUint::from(4)));
+        assert_eq!(BigUint::from(17).modinv(&BigUint::from(3120)), Some(BigUint::from(2753)));
+        assert_eq!(BigUint::from(6).modinv(&BigUint::from(9)), None);
+        assert_eq!(BigUint::from(1071).gcd(&BigUint::from(462)), BigUint::from(21));
+
+        // Fermat's little theorem for the Mersenne prime 2^127 - 1
+        let p = &(&BigUint::one() << 127) - &BigUint::one();
+        let a = big("123456789123456789123456789");
+        assert_eq!(a.modpow(&(&p - &BigUint::one()), &p), BigUint::one());
+        assert_eq!(&(&a * &a.modinv(&p).unwrap()) % &p, BigUint::one());
+    }
+
+    #[test]
+    pub fn test_primality() {
+        let mut rng = Xorshift64::new(3);
+        let primes = ["2", "97", "7919", "2147483647", "170141183460469231731687303715884105727"];
+        for p in primes.iter() {
+            assert!(big(p).is_probable_prime(20, &mut rng), "{} is prime", p);
+        }
+
+        // 561 is a Carmichael number, last one is a product of two 64-bit primes
+        let composites = ["1", "561", "7917", "2147483649", "340282366920938463463374607431768211457",
+                          "340282366920938460843936948965011886881"];
+        for c in composites.iter() {
+            assert!(!big(c).is_probable_prime(20, &mut rng), "{} is composite", c);
+        }
+    }
+
+    #[test]
+    pub fn test_signed() {
+        let a: BigInt = "-7".parse().unwrap();
+        let b = BigInt::from(2i64);
+        assert_eq!(&a / &b, BigInt::from(-3i64));
+        assert_eq!(&a % &b, BigInt::from(-1i64));
+        assert_eq!(&a + &b, BigInt::from(-5i64));
+        assert_eq!(&b - &a, BigInt::from(9i64));
+        assert_eq!(&a * &a, BigInt::from(49i64));
+        assert_eq!(a.rem_euclid(&BigUint::from(5)), BigUint::from(3));
+        assert_eq!((&a - &a).to_string(), "0");
+        assert_eq!(a.to_string(), "-7");
+        assert!(a < b);
+    }
+}
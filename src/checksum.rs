@@ -1,6 +1,11 @@
 /// simplest hash sum possible
 pub fn parity_bit(data: Vec<u8>) -> String {
-    format!("{:02x}", data.iter().fold(0u8, |a, &b| a ^ b))
+    format!("{:02x}", parity_byte(&data))
+}
+
+/// Same as `parity_bit`, but returns the raw byte.
+pub fn parity_byte(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |a, &b| a ^ b)
 }
 
 
@@ -37,15 +42,20 @@ pub mod sha {
     /// Also check this for explanation, step-by-step computation and stuff:
     /// https://csrc.nist.gov/csrc/media/publications/fips/180/2/archive/2002-08-01/documents/fips180-2.pdf
     pub fn sha256(data: Vec<u8>) -> String {
+        crate::utils::to_hex(&sha256_digest(&data))
+    }
+
+    /// Same as `sha256`, but returns raw digest bytes.
+    pub fn sha256_digest(data: &[u8]) -> [u8; 32] {
         let (mut h0, mut h1, mut h2, mut h3, mut h4, mut h5, mut h6, mut h7) = INIT;
-        let mut data = data;
+        let mut data = data.to_vec();
         let l: u64 = (data.len() * 8) as u64;
 
         data.push(0x80u8);
 
         let n_bits = (data.len() + 8) * 8;
 
-        let need_more_bits = if (n_bits) % 512 == 0 { 0 } else { 512 - n_bits % 512 };
+        let need_more_bits = if n_bits.is_multiple_of(512) { 0 } else { 512 - n_bits % 512 };
         if need_more_bits != 0 {
             // need_more_bits guaranteed to be divisible by 8 here because n % 512 is div. by
             // 8 if n is div by 8, and n is data.len() * 8; 512 - (something div. by 8) is
@@ -102,12 +112,166 @@ pub mod sha {
             h7 = h7.wrapping_add(h);
         }
 
-        format!("{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}", h0, h1, h2, h3, h4, h5, h6, h7)
+        let mut digest = [0u8; 32];
+        for (i, h) in [h0, h1, h2, h3, h4, h5, h6, h7].iter().enumerate() {
+            digest[4 * i..4 * i + 4].copy_from_slice(&h.to_be_bytes());
+        }
+        digest
+    }
+
+    const K512: [u64; 80] = [
+        0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+        0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+        0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+        0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+        0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+        0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+        0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+        0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+        0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+        0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+        0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+        0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+        0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+        0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+        0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+        0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+        0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+        0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+        0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+        0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+    ];
+
+    const INIT512: [u64; 8] = [
+        0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+        0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+    ];
+
+    const INIT1: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    /// Merkle–Damgård padding: 0x80, zeros, then message length in bits, big-endian,
+    /// in the last `length_size` bytes of the final block.
+    fn md_pad(data: &[u8], block_size: usize, length_size: usize) -> Vec<u8> {
+        let mut padded = data.to_vec();
+        padded.push(0x80);
+        while !(padded.len() + length_size).is_multiple_of(block_size) {
+            padded.push(0);
+        }
+        let bits = (data.len() as u128) * 8;
+        padded.extend_from_slice(&bits.to_be_bytes()[16 - length_size..]);
+        padded
+    }
+
+    /// SHA-1 (FIPS 180-4). Broken for collision resistance, here only for HMAC-SHA1,
+    /// which authenticator apps still use.
+    pub fn sha1_digest(data: &[u8]) -> [u8; 20] {
+        let mut h = INIT1;
+        let mut w = [0u32; 80];
+
+        for chunk in md_pad(data, 64, 8).chunks(64) {
+            for (i, word) in chunk.chunks(4).enumerate() {
+                w[i] = u32::from_be_bytes(word.try_into().unwrap());
+            }
+            for i in 16..80 {
+                w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+            }
+
+            let [mut a, mut b, mut c, mut d, mut e] = h;
+            for (i, &wi) in w.iter().enumerate() {
+                let (f, k) = match i {
+                    0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                    20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                    40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                    _ => (b ^ c ^ d, 0xca62c1d6),
+                };
+                let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+                e = d;
+                d = c;
+                c = b.rotate_left(30);
+                b = a;
+                a = temp;
+            }
+
+            for (h, v) in h.iter_mut().zip([a, b, c, d, e].iter()) {
+                *h = h.wrapping_add(*v);
+            }
+        }
+
+        let mut digest = [0u8; 20];
+        for (i, h) in h.iter().enumerate() {
+            digest[4 * i..4 * i + 4].copy_from_slice(&h.to_be_bytes());
+        }
+        digest
+    }
+
+    /// SHA-512 (FIPS 180-4): same structure as SHA-256 with 64-bit words and 80 rounds.
+    pub fn sha512_digest(data: &[u8]) -> [u8; 64] {
+        let mut h = INIT512;
+        let mut w = [0u64; 80];
+
+        for chunk in md_pad(data, 128, 16).chunks(128) {
+            for (i, word) in chunk.chunks(8).enumerate() {
+                w[i] = u64::from_be_bytes(word.try_into().unwrap());
+            }
+            for i in 16..80 {
+                let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+                let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+                w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+            }
+
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+            for i in 0..80 {
+                let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+                let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+                let ch = (e & f) ^ (!e & g);
+                let maj = (a & b) ^ (a & c) ^ (b & c);
+                let temp1 = hh.wrapping_add(s1).wrapping_add(ch).wrapping_add(K512[i]).wrapping_add(w[i]);
+                let temp2 = s0.wrapping_add(maj);
+
+                hh = g;
+                g = f;
+                f = e;
+                e = d.wrapping_add(temp1);
+                d = c;
+                c = b;
+                b = a;
+                a = temp1.wrapping_add(temp2);
+            }
+
+            for (h, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh].iter()) {
+                *h = h.wrapping_add(*v);
+            }
+        }
+
+        let mut digest = [0u8; 64];
+        for (i, h) in h.iter().enumerate() {
+            digest[8 * i..8 * i + 8].copy_from_slice(&h.to_be_bytes());
+        }
+        digest
     }
 
     #[cfg(test)]
     mod tests {
-        use super::sha256;
+        use super::*;
+        use crate::utils::to_hex;
+
+        #[test]
+        pub fn test_sha1() {
+            assert_eq!(to_hex(&sha1_digest(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+            assert_eq!(to_hex(&sha1_digest(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+            assert_eq!(to_hex(&sha1_digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+                       "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+        }
+
+        #[test]
+        pub fn test_sha512() {
+            assert_eq!(to_hex(&sha512_digest(b"abc")),
+                       "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                        2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+            assert_eq!(to_hex(&sha512_digest(b"")),
+                       "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                        47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e");
+        }
 
         #[test]
         pub fn test_sha_empty_str() {
@@ -132,3 +296,80 @@ pub mod sha {
 
     }
 }
+
+pub mod hmac {
+    use super::sha::{sha1_digest, sha256_digest, sha512_digest};
+
+    const SHA1_BLOCK_SIZE: usize = 64;
+    const SHA256_BLOCK_SIZE: usize = 64;
+    const SHA512_BLOCK_SIZE: usize = 128;
+
+    /// HMAC (RFC 2104) over an arbitrary hash function with given block size.
+    pub fn hmac<H: Fn(&[u8]) -> Vec<u8>>(hash: H, block_size: usize, key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut block_key = if key.len() > block_size { hash(key) } else { key.to_vec() };
+        block_key.resize(block_size, 0);
+
+        let mut inner = block_key.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>();
+        inner.extend_from_slice(data);
+
+        let mut outer = block_key.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>();
+        outer.extend_from_slice(&hash(&inner));
+
+        hash(&outer)
+    }
+
+    pub fn hmac_sha1(key: &[u8], data: &[u8]) -> [u8; 20] {
+        let mut mac = [0u8; 20];
+        mac.copy_from_slice(&hmac(|d| sha1_digest(d).to_vec(), SHA1_BLOCK_SIZE, key, data));
+        mac
+    }
+
+    pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+        let mut mac = [0u8; 32];
+        mac.copy_from_slice(&hmac(|d| sha256_digest(d).to_vec(), SHA256_BLOCK_SIZE, key, data));
+        mac
+    }
+
+    pub fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+        let mut mac = [0u8; 64];
+        mac.copy_from_slice(&hmac(|d| sha512_digest(d).to_vec(), SHA512_BLOCK_SIZE, key, data));
+        mac
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::utils::to_hex;
+
+        // RFC 4231, test cases 1 and 2
+        #[test]
+        pub fn test_hmac_sha256_rfc4231() {
+            assert_eq!(to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+                       "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+            assert_eq!(to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+                       "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        }
+
+        // RFC 4231, test case 6: key longer than block size
+        #[test]
+        pub fn test_hmac_sha256_long_key() {
+            assert_eq!(to_hex(&hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+                       "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+        }
+
+        // RFC 4231, test case 2
+        #[test]
+        pub fn test_hmac_sha512_rfc4231() {
+            assert_eq!(to_hex(&hmac_sha512(b"Jefe", b"what do ya want for nothing?")),
+                       "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                        9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737");
+        }
+
+        // RFC 2202, test case 2
+        #[test]
+        pub fn test_hmac_sha1_rfc2202() {
+            assert_eq!(to_hex(&hmac_sha1(b"Jefe", b"what do ya want for nothing?")),
+                       "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use crate::aes::{self, Aes, BLOCK_SIZE};
+use crate::utils::{self, AlgorithmError};
+use crate::xor::xor_inplace;
+
+/// Symmetric cipher operating on whole messages. Output may be longer than input
+/// (padding, IV etc), so data is passed as a vector.
+pub trait Cipher {
+    fn name(&self) -> &'static str;
+
+    fn encrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError>;
+
+    fn decrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError>;
+}
+
+/// Cipher which combines data with a keystream. Encryption and decryption are the same
+/// operation, and successive calls continue the keystream, so data can be processed in chunks.
+pub trait StreamCipher {
+    fn apply_keystream(&mut self, data: &mut [u8]);
+}
+
+const STREAM_CHUNK_SIZE: usize = 1 << 16;
+
+/// Encrypts (or decrypts) everything from input into output, returns the number of bytes processed.
+pub fn apply_stream<R, W>(cipher: &mut dyn StreamCipher, mut input: R, mut output: W) -> io::Result<u64>
+    where R: Read, W: Write
+{
+    let mut buf = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut total = 0u64;
+
+    loop {
+        let n = input.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        cipher.apply_keystream(&mut buf[..n]);
+        output.write_all(&buf[..n])?;
+        total += n as u64;
+    }
+
+    output.flush()?;
+    Ok(total)
+}
+
+/// Repeating-key XOR.
+pub struct Xor {
+    key: Vec<u8>,
+    position: usize,
+}
+
+impl Xor {
+    pub fn new(key: &[u8]) -> Result<Xor, AlgorithmError> {
+        if key.is_empty() {
+            return Err(AlgorithmError { what: "XOR key must not be empty".to_string() });
+        }
+        Ok(Xor { key: key.to_vec(), position: 0 })
+    }
+}
+
+impl Cipher for Xor {
+    fn name(&self) -> &'static str {
+        "xor"
+    }
+
+    fn encrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        xor_inplace(data, &self.key);
+        Ok(())
+    }
+
+    fn decrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        self.encrypt(data)
+    }
+}
+
+impl StreamCipher for Xor {
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for b in data.iter_mut() {
+            *b ^= self.key[self.position];
+            self.position = (self.position + 1) % self.key.len();
+        }
+    }
+}
+
+fn split_iv(data: &mut Vec<u8>) -> Result<aes::Block, AlgorithmError> {
+    if data.len() < BLOCK_SIZE {
+        return Err(AlgorithmError { what: "Ciphertext is too short to contain IV".to_string() });
+    }
+    let iv = data[..BLOCK_SIZE].try_into().unwrap();
+    data.drain(..BLOCK_SIZE);
+    Ok(iv)
+}
+
+fn random_iv() -> Result<aes::Block, AlgorithmError> {
+    utils::random_bytes(BLOCK_SIZE)
+        .map(|iv| iv.try_into().unwrap())
+        .map_err(|e| AlgorithmError { what: format!("Can't generate IV: {}", e) })
+}
+
+/// AES in ECB mode with PKCS#7 padding.
+pub struct AesEcb {
+    cipher: Aes,
+}
+
+impl AesEcb {
+    pub fn new(key: &[u8]) -> Result<AesEcb, AlgorithmError> {
+        Ok(AesEcb { cipher: Aes::new(key)? })
+    }
+}
+
+impl Cipher for AesEcb {
+    fn name(&self) -> &'static str {
+        "aes-ecb"
+    }
+
+    fn encrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        *data = aes::ecb::encrypt(&self.cipher, data);
+        Ok(())
+    }
+
+    fn decrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        *data = aes::ecb::decrypt(&self.cipher, data)?;
+        Ok(())
+    }
+}
+
+/// AES in CBC mode with PKCS#7 padding. Random IV is prepended to the ciphertext.
+pub struct AesCbc {
+    cipher: Aes,
+}
+
+impl AesCbc {
+    pub fn new(key: &[u8]) -> Result<AesCbc, AlgorithmError> {
+        Ok(AesCbc { cipher: Aes::new(key)? })
+    }
+}
+
+impl Cipher for AesCbc {
+    fn name(&self) -> &'static str {
+        "aes-cbc"
+    }
+
+    fn encrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        let iv = random_iv()?;
+        let ciphertext = aes::cbc::encrypt(&self.cipher, &iv, data);
+        *data = iv.to_vec();
+        data.extend(ciphertext);
+        Ok(())
+    }
+
+    fn decrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        let iv = split_iv(data)?;
+        *data = aes::cbc::decrypt(&self.cipher, &iv, data)?;
+        Ok(())
+    }
+}
+
+/// AES in CTR mode. As a `Cipher`, prepends random initial counter block to the ciphertext;
+/// as a `StreamCipher`, starts from the counter block given to `with_counter`, or from a random
+/// one picked by `new`, which the other side needs from `initial_counter`.
+pub struct AesCtr {
+    cipher: Aes,
+    initial_counter: aes::Block,
+    counter: u128,
+    keystream: aes::Block,
+    used: usize,
+}
+
+impl AesCtr {
+    pub fn new(key: &[u8]) -> Result<AesCtr, AlgorithmError> {
+        AesCtr::with_counter(key, &random_iv()?)
+    }
+
+    pub fn with_counter(key: &[u8], counter: &aes::Block) -> Result<AesCtr, AlgorithmError> {
+        Ok(AesCtr {
+            cipher: Aes::new(key)?,
+            initial_counter: *counter,
+            counter: u128::from_be_bytes(*counter),
+            keystream: [0u8; BLOCK_SIZE],
+            used: BLOCK_SIZE,
+        })
+    }
+
+    /// Counter block the keystream starts from
+    pub fn initial_counter(&self) -> &aes::Block {
+        &self.initial_counter
+    }
+}
+
+impl Cipher for AesCtr {
+    fn name(&self) -> &'static str {
+        "aes-ctr"
+    }
+
+    fn encrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        let iv = random_iv()?;
+        aes::ctr::apply_keystream(&self.cipher, &iv, data);
+        data.splice(0..0, iv.iter().copied());
+        Ok(())
+    }
+
+    fn decrypt(&self, data: &mut Vec<u8>) -> Result<(), AlgorithmError> {
+        let iv = split_iv(data)?;
+        aes::ctr::apply_keystream(&self.cipher, &iv, data);
+        Ok(())
+    }
+}
+
+impl StreamCipher for AesCtr {
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for b in data.iter_mut() {
+            if self.used == BLOCK_SIZE {
+                self.keystream = self.counter.to_be_bytes();
+                self.cipher.encrypt_block(&mut self.keystream);
+                self.counter = self.counter.wrapping_add(1);
+                self.used = 0;
+            }
+            *b ^= self.keystream[self.used];
+            self.used += 1;
+        }
+    }
+}
+
+/// Names of all ciphers known to `by_name`.
+pub const CIPHER_NAMES: [&str; 4] = ["xor", "aes-ecb", "aes-cbc", "aes-ctr"];
+
+/// Creates a cipher by its name, validating the key. `aes` is accepted as an alias for `aes-cbc`.
+pub fn by_name(name: &str, key: &[u8]) -> Result<Box<dyn Cipher>, AlgorithmError> {
+    Ok(match name.to_lowercase().as_str() {
+        "xor" => Box::new(Xor::new(key)?),
+        "aes-ecb" => Box::new(AesEcb::new(key)?),
+        "aes-cbc" | "aes" => Box::new(AesCbc::new(key)?),
+        "aes-ctr" => Box::new(AesCtr::new(key)?),
+        other => return Err(AlgorithmError {
+            what: format!("Unknown cipher: {} (known ciphers: {})", other, CIPHER_NAMES.join(", "))
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8; 16] = b"YELLOW SUBMARINE";
+
+    #[test]
+    pub fn test_registry_roundtrip() {
+        for name in CIPHER_NAMES.iter() {
+            let cipher = by_name(name, KEY).unwrap();
+            assert_eq!(&cipher.name(), name);
+
+            let mut data = b"some text that is longer than one block".to_vec();
+            cipher.encrypt(&mut data).unwrap();
+            assert_ne!(data, b"some text that is longer than one block".to_vec());
+            cipher.decrypt(&mut data).unwrap();
+            assert_eq!(data, b"some text that is longer than one block".to_vec());
+        }
+    }
+
+    #[test]
+    pub fn test_invalid_keys() {
+        assert!(by_name("xor", b"").is_err());
+        assert!(by_name("aes-cbc", b"too short").is_err());
+        assert!(by_name("rot13", KEY).is_err());
+    }
+
+    #[test]
+    pub fn test_stream_chunks_match_whole_message() {
+        let text = (0..100u8).collect::<Vec<u8>>();
+
+        let mut whole = text.clone();
+        xor_inplace(&mut whole, b"abc");
+
+        let mut xor = Xor::new(b"abc").unwrap();
+        let mut chunked = text.clone();
+        for chunk in chunked.chunks_mut(7) {
+            xor.apply_keystream(chunk);
+        }
+        assert_eq!(chunked, whole);
+
+        let counter = [3u8; BLOCK_SIZE];
+        let mut whole = text.clone();
+        aes::ctr::apply_keystream(&Aes::new(KEY).unwrap(), &counter, &mut whole);
+
+        let mut ctr = AesCtr::with_counter(KEY, &counter).unwrap();
+        let mut streamed = Vec::new();
+        apply_stream(&mut ctr, &text[..], &mut streamed).unwrap();
+        assert_eq!(streamed, whole);
+
+        let mut encryptor = AesCtr::new(KEY).unwrap();
+        assert_ne!(encryptor.initial_counter(), AesCtr::new(KEY).unwrap().initial_counter());
+        let mut decryptor = AesCtr::with_counter(KEY, encryptor.initial_counter()).unwrap();
+        let mut data = text.clone();
+        encryptor.apply_keystream(&mut data);
+        assert_ne!(data, text);
+        decryptor.apply_keystream(&mut data);
+        assert_eq!(data, text);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::utils::AlgorithmError;
+
+/// Ordered set of letters the classical ciphers operate on. Letters are matched
+/// case-insensitively, and the case of each letter is preserved on output.
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    lower: Vec<char>,
+    upper: Vec<char>,
+    index: HashMap<char, usize>,
+}
+
+impl Alphabet {
+
+    /// Creates an alphabet from lowercase letters in order. Uppercase letters are derived
+    /// with `char::to_uppercase`.
+    pub fn new(letters: &str) -> Result<Alphabet, AlgorithmError> {
+        let lower = letters.chars().collect::<Vec<char>>();
+        let upper = lower
+            .iter()
+            .map(|c| c.to_uppercase().next().unwrap_or(*c))
+            .collect::<Vec<char>>();
+
+        let mut index = HashMap::with_capacity(2 * lower.len());
+        for (i, (&l, &u)) in lower.iter().zip(upper.iter()).enumerate() {
+            if index.insert(l, i).is_some() || (u != l && index.insert(u, i).is_some()) {
+                return Err(AlgorithmError { what: format!("Letter '{}' occurs in alphabet twice", l) });
+            }
+        }
+
+        if lower.len() < 2 {
+            return Err(AlgorithmError { what: "Alphabet must contain at least 2 letters".to_string() });
+        }
+
+        Ok(Alphabet { lower, upper, index })
+    }
+
+    /// Modern Russian alphabet, including ё
+    pub fn russian() -> Alphabet {
+        Alphabet::new("абвгдеёжзийклмнопрстуфхцчшщъыьэюя").unwrap()
+    }
+
+    /// English alphabet
+    pub fn english() -> Alphabet {
+        Alphabet::new("abcdefghijklmnopqrstuvwxyz").unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lower.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.is_empty()
+    }
+
+    /// All letters, in lowercase
+    pub fn letters(&self) -> &[char] {
+        &self.lower
+    }
+
+    /// Position of a letter in alphabet, regardless of its case
+    pub fn index_of(&self, c: char) -> Option<usize> {
+        self.index.get(&c).copied()
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.index.contains_key(&c)
+    }
+
+    /// Letter at a given position, in lowercase
+    pub fn letter(&self, i: usize) -> char {
+        self.lower[i]
+    }
+
+    /// Replaces every letter of the text by a letter with index `f(index)`, preserving case.
+    /// Other characters are kept as is. `f` is called only for letters, in order.
+    pub fn map_letters<F>(&self, text: &str, mut f: F) -> String
+        where F: FnMut(usize) -> usize
+    {
+        text
+            .chars()
+            .map(|c| match self.index_of(c) {
+                Some(i) => {
+                    let j = f(i) % self.len();
+                    if self.upper[i] == c && self.lower[i] != c { self.upper[j] } else { self.lower[j] }
+                },
+                None => c,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ru" | "russian" => Ok(Alphabet::russian()),
+            "en" | "english" => Ok(Alphabet::english()),
+            _ => Alphabet::new(s).map_err(|AlgorithmError { what }| what),
+        }
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Shift cipher: every letter is moved `shift` positions forward.
+pub mod caesar {
+    use super::*;
+
+    pub fn encrypt(text: &str, alphabet: &Alphabet, shift: usize) -> String {
+        alphabet.map_letters(text, |i| i + shift % alphabet.len())
+    }
+
+    pub fn decrypt(text: &str, alphabet: &Alphabet, shift: usize) -> String {
+        alphabet.map_letters(text, |i| i + alphabet.len() - shift % alphabet.len())
+    }
+}
+
+/// Affine cipher: letter with index x is replaced by letter with index (a * x + b) mod n.
+/// `a` must be coprime with the alphabet length, otherwise decryption is ambiguous.
+pub mod affine {
+    use super::*;
+
+    fn check_key(alphabet: &Alphabet, a: usize) -> Result<usize, AlgorithmError> {
+        let n = alphabet.len();
+        (1..n)
+            .find(|inv| (a % n) * inv % n == 1)
+            .ok_or_else(|| AlgorithmError {
+                what: format!("Key a = {} is not coprime with alphabet length {} (gcd is {})", a, n, gcd(a, n))
+            })
+    }
+
+    pub fn encrypt(text: &str, alphabet: &Alphabet, a: usize, b: usize) -> Result<String, AlgorithmError> {
+        check_key(alphabet, a)?;
+        let n = alphabet.len();
+        Ok(alphabet.map_letters(text, |x| (a % n) * x + b % n))
+    }
+
+    pub fn decrypt(text: &str, alphabet: &Alphabet, a: usize, b: usize) -> Result<String, AlgorithmError> {
+        let a_inv = check_key(alphabet, a)?;
+        let n = alphabet.len();
+        Ok(alphabet.map_letters(text, |y| a_inv * (y + n - b % n)))
+    }
+}
+
+/// Vigenère cipher: letters are shifted by the letters of a repeated key. Key position advances
+/// only on letters of the alphabet, so spaces and punctuation do not consume the key.
+pub mod vigenere {
+    use super::*;
+
+    /// Converts key into a list of shifts, checking that all of its characters are letters
+    pub fn key_shifts(key: &str, alphabet: &Alphabet) -> Result<Vec<usize>, AlgorithmError> {
+        if key.is_empty() {
+            return Err(AlgorithmError { what: "Vigenère key must not be empty".to_string() });
+        }
+        key
+            .chars()
+            .map(|c| alphabet.index_of(c).ok_or_else(|| AlgorithmError {
+                what: format!("Key character '{}' is not in the alphabet", c)
+            }))
+            .collect()
+    }
+
+    pub fn encrypt(text: &str, alphabet: &Alphabet, key: &str) -> Result<String, AlgorithmError> {
+        let shifts = key_shifts(key, alphabet)?;
+        let mut key_iter = shifts.iter().cycle();
+        Ok(alphabet.map_letters(text, |i| i + key_iter.next().unwrap()))
+    }
+
+    pub fn decrypt(text: &str, alphabet: &Alphabet, key: &str) -> Result<String, AlgorithmError> {
+        let shifts = key_shifts(key, alphabet)?;
+        let mut key_iter = shifts.iter().cycle();
+        Ok(alphabet.map_letters(text, |i| i + alphabet.len() - key_iter.next().unwrap()))
+    }
+}
+
+/// Simple substitution cipher: key is a permutation of the alphabet, i-th letter of the
+/// alphabet is replaced by i-th letter of the key.
+pub mod substitution {
+    use super::*;
+
+    /// Converts key into a permutation of letter indices, checking that it is one
+    pub fn key_permutation(key: &str, alphabet: &Alphabet) -> Result<Vec<usize>, AlgorithmError> {
+        let permutation = key
+            .chars()
+            .map(|c| alphabet.index_of(c).ok_or_else(|| AlgorithmError {
+                what: format!("Key character '{}' is not in the alphabet", c)
+            }))
+            .collect::<Result<Vec<usize>, AlgorithmError>>()?;
+
+        let mut seen = vec![false; alphabet.len()];
+        permutation.iter().for_each(|&i| seen[i] = true);
+        if permutation.len() != alphabet.len() || seen.iter().any(|s| !s) {
+            return Err(AlgorithmError {
+                what: format!("Substitution key must contain each of {} letters exactly once", alphabet.len())
+            });
+        }
+
+        Ok(permutation)
+    }
+
+    /// Inverts a permutation, turning encryption key into decryption key and vice versa
+    pub fn invert(permutation: &[usize]) -> Vec<usize> {
+        let mut result = vec![0; permutation.len()];
+        permutation.iter().enumerate().for_each(|(i, &j)| result[j] = i);
+        result
+    }
+
+    pub fn encrypt(text: &str, alphabet: &Alphabet, key: &str) -> Result<String, AlgorithmError> {
+        let permutation = key_permutation(key, alphabet)?;
+        Ok(alphabet.map_letters(text, |i| permutation[i]))
+    }
+
+    pub fn decrypt(text: &str, alphabet: &Alphabet, key: &str) -> Result<String, AlgorithmError> {
+        let permutation = invert(&key_permutation(key, alphabet)?);
+        Ok(alphabet.map_letters(text, |i| permutation[i]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::is_russian_char;
+
+    #[test]
+    pub fn test_russian_alphabet() {
+        let ru = Alphabet::russian();
+        assert_eq!(ru.len(), 33);
+        assert_eq!(ru.index_of('Ё'), Some(6));
+        assert!((0x0400..0x0460u32).filter_map(std::char::from_u32).all(|c| ru.contains(c) == is_russian_char(c)));
+    }
+
+    #[test]
+    pub fn test_caesar() {
+        let en = Alphabet::english();
+        assert_eq!(caesar::encrypt("Hello, World!", &en, 3), "Khoor, Zruog!");
+        assert_eq!(caesar::decrypt("Khoor, Zruog!", &en, 3), "Hello, World!");
+
+        let ru = Alphabet::russian();
+        assert_eq!(caesar::encrypt("Привет, Яна!", &ru, 1), "Рсйгёу, Аоб!");
+        assert_eq!(caesar::decrypt("Рсйгёу, Аоб!", &ru, 34), "Привет, Яна!");
+    }
+
+    #[test]
+    pub fn test_affine() {
+        let en = Alphabet::english();
+        assert_eq!(affine::encrypt("AFFINE cipher", &en, 5, 8).unwrap(), "IHHWVC swfrcp");
+        assert_eq!(affine::decrypt("IHHWVC swfrcp", &en, 5, 8).unwrap(), "AFFINE cipher");
+
+        let ru = Alphabet::russian();
+        let text = "Съешь же ещё этих мягких французских булок, да выпей чаю.";
+        let encrypted = affine::encrypt(text, &ru, 4, 7).unwrap();
+        assert_eq!(affine::decrypt(&encrypted, &ru, 4, 7).unwrap(), text);
+
+        assert!(affine::encrypt(text, &ru, 3, 7).is_err());
+        assert!(affine::encrypt(text, &en, 13, 7).is_err());
+    }
+
+    #[test]
+    pub fn test_vigenere() {
+        let en = Alphabet::english();
+        assert_eq!(vigenere::encrypt("Attack at dawn", &en, "LEMON").unwrap(), "Lxfopv ef rnhr");
+        assert_eq!(vigenere::decrypt("Lxfopv ef rnhr", &en, "lemon").unwrap(), "Attack at dawn");
+
+        let ru = Alphabet::russian();
+        let text = "Съешь же ещё этих мягких французских булок, да выпей чаю.";
+        let encrypted = vigenere::encrypt(text, &ru, "ключ").unwrap();
+        assert_eq!(vigenere::decrypt(&encrypted, &ru, "КЛЮЧ").unwrap(), text);
+
+        assert!(vigenere::encrypt(text, &ru, "key").is_err());
+        assert!(vigenere::encrypt(text, &ru, "").is_err());
+    }
+
+    #[test]
+    pub fn test_substitution() {
+        let en = Alphabet::english();
+        let key = "qwertyuiopasdfghjklzxcvbnm";
+        assert_eq!(substitution::encrypt("Hello, World!", &en, key).unwrap(), "Itssg, Vgksr!");
+        assert_eq!(substitution::decrypt("Itssg, Vgksr!", &en, key).unwrap(), "Hello, World!");
+
+        assert!(substitution::encrypt("text", &en, "abc").is_err());
+        assert!(substitution::encrypt("text", &en, "aacdefghijklmnopqrstuvwxyz").is_err());
+    }
+}
@@ -0,0 +1,430 @@
+//! Binary-to-text encodings. Every decoder has two modes:
+//!
+//! - `Strict` accepts only the canonical form produced by the encoder: no whitespace, exact
+//!   padding, one letter case, zero unused bits;
+//! - `Lenient` accepts what people paste: whitespace anywhere, missing padding, any letter case
+//!   where the alphabet allows it, and the usual decorations (`0x`, `<~ ~>`).
+
+use std::str::FromStr;
+
+use crate::utils::AlgorithmError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strictness {
+    Strict,
+    Lenient,
+}
+
+fn invalid(encoding: &str, what: &str) -> AlgorithmError {
+    AlgorithmError { what: format!("Invalid {}: {}", encoding, what) }
+}
+
+/// Symbols of the string, without whitespace in lenient mode
+fn symbols(s: &str, strictness: Strictness) -> Vec<u8> {
+    match strictness {
+        Strictness::Strict => s.bytes().collect(),
+        Strictness::Lenient => s.bytes().filter(|b| !b.is_ascii_whitespace()).collect(),
+    }
+}
+
+/// Splits data and trailing padding, checking that the amount of padding is right for the
+/// block size in strict mode, or absent if the encoding is unpadded.
+fn split_padding<'a>(
+    name: &str, symbols: &'a [u8], pad: u8, block: usize, padded: bool, strictness: Strictness
+) -> Result<&'a [u8], AlgorithmError> {
+    let data_len = symbols.iter().rposition(|&b| b != pad).map_or(0, |i| i + 1);
+    let padding = symbols.len() - data_len;
+    let expected = if padded { (block - data_len % block) % block } else { 0 };
+    if padding >= block || (strictness == Strictness::Strict && padding != expected) {
+        return Err(invalid(name, "wrong padding"));
+    }
+    Ok(&symbols[..data_len])
+}
+
+/// Decodes symbols of `bits`-bit values, most significant first, as in Base64 and Base32.
+fn decode_bits<F>(
+    name: &str, symbols: &[u8], bits: usize, strictness: Strictness, value: F
+) -> Result<Vec<u8>, AlgorithmError>
+    where F: Fn(u8) -> Option<u8>
+{
+    if (symbols.len() * bits) % 8 >= bits {
+        return Err(invalid(name, "truncated data"));
+    }
+
+    let mut result = Vec::with_capacity(symbols.len() * bits / 8);
+    let (mut acc, mut acc_bits) = (0u32, 0);
+    for &b in symbols.iter() {
+        let v = value(b).ok_or_else(|| invalid(name, &format!("unexpected character {:?}", b as char)))?;
+        acc = (acc << bits | v as u32) & 0xffff;
+        acc_bits += bits;
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            result.push((acc >> acc_bits) as u8);
+        }
+    }
+    if strictness == Strictness::Strict && acc & ((1 << acc_bits) - 1) != 0 {
+        return Err(invalid(name, "non-zero unused bits"));
+    }
+    Ok(result)
+}
+
+/// Encodes data as symbols of `bits`-bit values, most significant first
+fn encode_bits(data: &[u8], bits: usize, alphabet: &[u8]) -> String {
+    let mut result = String::with_capacity((data.len() * 8).div_ceil(bits));
+    let (mut acc, mut acc_bits) = (0u32, 0);
+    for &b in data.iter() {
+        acc = (acc << 8 | b as u32) & 0xffff;
+        acc_bits += 8;
+        while acc_bits >= bits {
+            acc_bits -= bits;
+            result.push(alphabet[(acc >> acc_bits) as usize & ((1 << bits) - 1)] as char);
+        }
+    }
+    if acc_bits > 0 {
+        result.push(alphabet[(acc << (bits - acc_bits)) as usize & ((1 << bits) - 1)] as char);
+    }
+    result
+}
+
+/// Base16, lowercase.
+pub mod hex {
+    use super::*;
+
+    pub fn encode(data: &[u8]) -> String {
+        encode_bits(data, 4, b"0123456789abcdef")
+    }
+
+    /// Lenient mode accepts any case, `0x` prefix, and whitespace or `:` between bytes.
+    pub fn decode(s: &str, strictness: Strictness) -> Result<Vec<u8>, AlgorithmError> {
+        let mut symbols = symbols(s, strictness);
+        if strictness == Strictness::Lenient {
+            if symbols.starts_with(b"0x") || symbols.starts_with(b"0X") {
+                symbols.drain(..2);
+            }
+            symbols.retain(|&b| b != b':');
+        }
+        if !symbols.len().is_multiple_of(2) {
+            return Err(invalid("hex", "odd number of digits"));
+        }
+
+        decode_bits("hex", &symbols, 4, strictness, |b| match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' if strictness == Strictness::Lenient => Some(b - b'A' + 10),
+            _ => None,
+        })
+    }
+}
+
+/// Base64 (RFC 4648, sections 4 and 5).
+pub mod base64 {
+    use super::*;
+
+    const PAD: u8 = b'=';
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Variant {
+        alphabet: &'static [u8; 64],
+        padded: bool,
+    }
+
+    const STANDARD_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    const URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    pub const STANDARD: Variant = Variant { alphabet: STANDARD_ALPHABET, padded: true };
+    pub const STANDARD_NO_PAD: Variant = Variant { alphabet: STANDARD_ALPHABET, padded: false };
+    pub const URL: Variant = Variant { alphabet: URL_ALPHABET, padded: true };
+    pub const URL_NO_PAD: Variant = Variant { alphabet: URL_ALPHABET, padded: false };
+
+    pub fn encode(data: &[u8], variant: &Variant) -> String {
+        let mut result = encode_bits(data, 6, variant.alphabet);
+        if variant.padded {
+            while !result.len().is_multiple_of(4) {
+                result.push(PAD as char);
+            }
+        }
+        result
+    }
+
+    /// Lenient mode also accepts symbols of the other alphabet (`+/` or `-_`), so that either
+    /// variant decodes with either setting.
+    pub fn decode(s: &str, variant: &Variant, strictness: Strictness) -> Result<Vec<u8>, AlgorithmError> {
+        let symbols = symbols(s, strictness);
+        let data = split_padding("Base64", &symbols, PAD, 4, variant.padded, strictness)?;
+
+        decode_bits("Base64", data, 6, strictness, |b| {
+            let position = variant.alphabet.iter().position(|&a| a == b);
+            let other = match (strictness, b) {
+                (Strictness::Lenient, b'+') | (Strictness::Lenient, b'-') => Some(62),
+                (Strictness::Lenient, b'/') | (Strictness::Lenient, b'_') => Some(63),
+                _ => None,
+            };
+            position.or(other).map(|p| p as u8)
+        })
+    }
+}
+
+/// Base32 (RFC 4648, section 6), as used for authenticator app secrets.
+pub mod base32 {
+    use super::*;
+
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    const PAD: u8 = b'=';
+
+    pub fn encode(data: &[u8]) -> String {
+        let mut result = encode_bits(data, 5, ALPHABET);
+        while !result.len().is_multiple_of(8) {
+            result.push(PAD as char);
+        }
+        result
+    }
+
+    /// Lenient mode accepts lowercase, since secrets are usually shown to users in lowercase
+    /// groups of four without padding.
+    pub fn decode(s: &str, strictness: Strictness) -> Result<Vec<u8>, AlgorithmError> {
+        let symbols = symbols(s, strictness);
+        let data = split_padding("Base32", &symbols, PAD, 8, true, strictness)?;
+
+        decode_bits("Base32", data, 5, strictness, |b| {
+            let b = if strictness == Strictness::Lenient { b.to_ascii_uppercase() } else { b };
+            ALPHABET.iter().position(|&a| a == b).map(|p| p as u8)
+        })
+    }
+}
+
+/// Ascii85 (btoa / Adobe flavour): every 4 bytes become 5 base-85 digits `!`..`u`, an all-zero
+/// group becomes `z`. Encoded without the `<~ ~>` delimiters.
+pub mod ascii85 {
+    use super::*;
+
+    const FIRST: u8 = b'!';
+    const ZERO_GROUP: u8 = b'z';
+
+    pub fn encode(data: &[u8]) -> String {
+        let mut result = String::with_capacity(data.len().div_ceil(4) * 5);
+        for chunk in data.chunks(4) {
+            let mut group = [0u8; 4];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let mut n = u32::from_be_bytes(group);
+            if n == 0 && chunk.len() == 4 {
+                result.push(ZERO_GROUP as char);
+                continue;
+            }
+
+            let mut digits = [0u8; 5];
+            for d in digits.iter_mut().rev() {
+                *d = FIRST + (n % 85) as u8;
+                n /= 85;
+            }
+            // partial group of k bytes is written as its first k + 1 digits
+            result.extend(digits[..chunk.len() + 1].iter().map(|&d| d as char));
+        }
+        result
+    }
+
+    /// Lenient mode accepts whitespace and `<~ ~>` delimiters.
+    pub fn decode(s: &str, strictness: Strictness) -> Result<Vec<u8>, AlgorithmError> {
+        let mut symbols = symbols(s, strictness);
+        if strictness == Strictness::Lenient {
+            if symbols.starts_with(b"<~") {
+                symbols.drain(..2);
+            }
+            if symbols.ends_with(b"~>") {
+                symbols.truncate(symbols.len() - 2);
+            }
+        }
+
+        let mut result = Vec::with_capacity(symbols.len() / 5 * 4);
+        let mut group = Vec::with_capacity(5);
+        let mut iter = symbols.iter().peekable();
+        while let Some(&b) = iter.next() {
+            match b {
+                ZERO_GROUP if group.is_empty() => result.extend_from_slice(&[0; 4]),
+                b'!'..=b'u' => group.push(b - FIRST),
+                _ => return Err(invalid("Ascii85", &format!("unexpected character {:?}", b as char))),
+            }
+            if group.len() < 5 && iter.peek().is_some() {
+                continue;
+            }
+            if group.len() == 1 {
+                return Err(invalid("Ascii85", "truncated data"));
+            }
+            if group.is_empty() {
+                break;
+            }
+
+            // partial group is padded with the highest digit, so that truncation gives its bytes back
+            let len = group.len();
+            group.resize(5, 84);
+            let n = group.iter().try_fold(0u32, |acc, &d| acc.checked_mul(85)?.checked_add(d as u32))
+                .ok_or_else(|| invalid("Ascii85", "group value overflows 32 bits"))?;
+            if strictness == Strictness::Strict && len == 5 && n == 0 {
+                return Err(invalid("Ascii85", "zero group must be written as 'z'"));
+            }
+            let bytes = &n.to_be_bytes()[..len - 1];
+            // several digit strings decode to the same bytes, only the one the encoder writes is canonical
+            if strictness == Strictness::Strict && len < 5
+                && encode(bytes).bytes().ne(group[..len].iter().map(|&d| d + FIRST)) {
+                return Err(invalid("Ascii85", "non-canonical final group"));
+            }
+            result.extend_from_slice(bytes);
+            group.clear();
+        }
+
+        Ok(result)
+    }
+}
+
+/// Encoding selectable by name, for command-line tools. `Raw` passes bytes through unchanged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Raw,
+    Hex,
+    Base64(base64::Variant),
+    Base32,
+    Ascii85,
+}
+
+pub const ENCODING_NAMES: [&str; 8] =
+    ["raw", "hex", "base64", "base64-nopad", "base64url", "base64url-nopad", "base32", "ascii85"];
+
+impl Encoding {
+    pub fn encode(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Encoding::Raw => data.to_vec(),
+            Encoding::Hex => hex::encode(data).into_bytes(),
+            Encoding::Base64(variant) => base64::encode(data, variant).into_bytes(),
+            Encoding::Base32 => base32::encode(data).into_bytes(),
+            Encoding::Ascii85 => ascii85::encode(data).into_bytes(),
+        }
+    }
+
+    pub fn decode(&self, data: &[u8], strictness: Strictness) -> Result<Vec<u8>, AlgorithmError> {
+        let text = || std::str::from_utf8(data).map_err(|_| AlgorithmError { what: "Encoded data is not text".to_string() });
+        match self {
+            Encoding::Raw => Ok(data.to_vec()),
+            Encoding::Hex => hex::decode(text()?, strictness),
+            Encoding::Base64(variant) => base64::decode(text()?, variant, strictness),
+            Encoding::Base32 => base32::decode(text()?, strictness),
+            Encoding::Ascii85 => ascii85::decode(text()?, strictness),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "raw" => Encoding::Raw,
+            "hex" => Encoding::Hex,
+            "base64" => Encoding::Base64(base64::STANDARD),
+            "base64-nopad" => Encoding::Base64(base64::STANDARD_NO_PAD),
+            "base64url" => Encoding::Base64(base64::URL),
+            "base64url-nopad" => Encoding::Base64(base64::URL_NO_PAD),
+            "base32" => Encoding::Base32,
+            "ascii85" => Encoding::Ascii85,
+            other => return Err(AlgorithmError {
+                what: format!("Unknown encoding: {} (known encodings: {})", other, ENCODING_NAMES.join(", "))
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::Strictness::{Lenient, Strict};
+
+    #[test]
+    pub fn test_hex() {
+        assert_eq!(hex::encode(&[0x00, 0xab, 0x10]), "00ab10");
+        assert_eq!(hex::decode("00ab10", Strict).unwrap(), vec![0x00, 0xab, 0x10]);
+        assert_eq!(hex::decode("0x00:AB:10\n", Lenient).unwrap(), vec![0x00, 0xab, 0x10]);
+        assert!(hex::decode("00AB10", Strict).is_err());
+        assert!(hex::decode("00ab1", Lenient).is_err());
+        assert!(hex::decode("00 ab", Strict).is_err());
+    }
+
+    // RFC 4648, section 10
+    #[test]
+    pub fn test_base64_rfc4648() {
+        let vectors = [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"),
+                       ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")];
+        for (plain, encoded) in vectors.iter() {
+            assert_eq!(base64::encode(plain.as_bytes(), &base64::STANDARD), *encoded);
+            assert_eq!(base64::decode(encoded, &base64::STANDARD, Strict).unwrap(), plain.as_bytes().to_vec());
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(base64::encode(plain.as_bytes(), &base64::STANDARD_NO_PAD), unpadded);
+            assert_eq!(base64::decode(unpadded, &base64::STANDARD_NO_PAD, Strict).unwrap(), plain.as_bytes().to_vec());
+        }
+
+        assert_eq!(base64::decode("Zm9v\nYmFy\n", &base64::STANDARD, Lenient).unwrap(), b"foobar".to_vec());
+        assert!(base64::decode("Zm9v\nYmFy\n", &base64::STANDARD, Strict).is_err());
+        assert!(base64::decode("Zm9", &base64::STANDARD, Strict).is_err());
+        assert_eq!(base64::decode("Zm9", &base64::STANDARD, Lenient).unwrap(), b"fo".to_vec());
+        assert!(base64::decode("Zg==Zm9v", &base64::STANDARD, Lenient).is_err());
+        assert!(base64::decode("Zm9!", &base64::STANDARD, Lenient).is_err());
+        assert!(base64::decode("Zh==", &base64::STANDARD, Strict).is_err());
+        assert!(base64::decode("Z===", &base64::STANDARD, Lenient).is_err());
+    }
+
+    #[test]
+    pub fn test_base64_url() {
+        let data = [0xfb, 0xff, 0xbf];
+        assert_eq!(base64::encode(&data, &base64::STANDARD), "+/+/");
+        assert_eq!(base64::encode(&data, &base64::URL), "-_-_");
+        assert!(base64::decode("+/+/", &base64::URL, Strict).is_err());
+        assert_eq!(base64::decode("+/-_", &base64::URL, Lenient).unwrap(), data.to_vec());
+        assert!(base64::decode("-_8=", &base64::URL_NO_PAD, Strict).is_err());
+    }
+
+    #[test]
+    pub fn test_base32_rfc4648() {
+        let vectors = [("", ""), ("f", "MY======"), ("fo", "MZXQ===="), ("foo", "MZXW6==="),
+                       ("foob", "MZXW6YQ="), ("fooba", "MZXW6YTB"), ("foobar", "MZXW6YTBOI======")];
+        for (plain, encoded) in vectors.iter() {
+            assert_eq!(base32::encode(plain.as_bytes()), *encoded);
+            assert_eq!(base32::decode(encoded, Strict).unwrap(), plain.as_bytes().to_vec());
+        }
+
+        assert_eq!(base32::decode("mzxw 6ytb oi", Lenient).unwrap(), b"foobar".to_vec());
+        assert!(base32::decode("mzxw6ytboi======", Strict).is_err());
+        assert!(base32::decode("MZXW6YTBOI", Strict).is_err());
+        assert!(base32::decode("MZXW6Y", Lenient).is_err());
+        assert!(base32::decode("MZXW1===", Lenient).is_err());
+    }
+
+    // checked against Python's base64.a85encode
+    #[test]
+    pub fn test_ascii85() {
+        let vectors: [(&[u8], &str); 8] = [
+            (b"", ""), (b"M", "9`"), (b"Ma", "9jn"), (b"Man", "9jqo"), (b"Man is", "9jqo^Bla"),
+            (b"\0\0\0\0\0", "z!!"), (b"\xff\xff\xff\xff", "s8W-!"), (b"hello world", "BOu!rD]j7BEbo7"),
+        ];
+        for (plain, encoded) in vectors.iter() {
+            assert_eq!(ascii85::encode(plain), *encoded);
+            assert_eq!(ascii85::decode(encoded, Strict).unwrap(), plain.to_vec());
+        }
+
+        assert_eq!(ascii85::decode("<~BOu!rD]j7\nBEbo7~>", Lenient).unwrap(), b"hello world".to_vec());
+        assert!(ascii85::decode("<~BOu!rD]j7BEbo7~>", Strict).is_err());
+        assert!(ascii85::decode("!!!!!", Strict).is_err());
+        assert_eq!(ascii85::decode("9`", Strict).unwrap(), vec![77]);
+        assert!(ascii85::decode("9a", Strict).is_err());
+        assert_eq!(ascii85::decode("9a", Lenient).unwrap(), vec![77]);
+        assert_eq!(ascii85::decode("!!!!!", Lenient).unwrap(), vec![0; 4]);
+        assert!(ascii85::decode("s8W-\"", Lenient).is_err());
+        assert!(ascii85::decode("9jqo^B", Lenient).is_err());
+    }
+
+    #[test]
+    pub fn test_encoding_by_name() {
+        let data = b"any carnal pleasure";
+        for name in ENCODING_NAMES.iter() {
+            let encoding: Encoding = name.parse().unwrap();
+            assert_eq!(encoding.decode(&encoding.encode(data), Strict).unwrap(), data.to_vec());
+        }
+        assert!("base58".parse::<Encoding>().is_err());
+    }
+}
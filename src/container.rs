@@ -0,0 +1,173 @@
+//! Encrypted file container.
+//!
+//! Layout (all integers are big-endian):
+//!
+//! | field      | size     |                                             |
+//! |------------|----------|---------------------------------------------|
+//! | magic      | 4        | `SECC`                                      |
+//! | version    | 1        | currently 1                                 |
+//! | kdf        | 1        | 1 = PBKDF2-HMAC-SHA256                      |
+//! | iterations | 4        | KDF iteration count                         |
+//! | salt       | 16       | KDF salt                                    |
+//! | nonce      | 16       | initial AES-CTR counter block               |
+//! | ciphertext | variable | AES-256-CTR                                 |
+//! | tag        | 32       | HMAC-SHA256 over everything above           |
+//!
+//! Passphrase is stretched into 64 bytes: first half is the encryption key, second half is
+//! the MAC key (encrypt-then-MAC).
+use std::convert::TryInto;
+
+use crate::aes::{self, Aes};
+use crate::checksum::hmac::hmac_sha256;
+use crate::kdf::pbkdf2_hmac_sha256;
+use crate::utils::{self, AlgorithmError};
+
+pub const MAGIC: &[u8; 4] = b"SECC";
+pub const VERSION: u8 = 1;
+
+const KDF_PBKDF2_HMAC_SHA256: u8 = 1;
+
+pub const SALT_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = aes::BLOCK_SIZE;
+pub const TAG_SIZE: usize = 32;
+pub const HEADER_SIZE: usize = 4 + 1 + 1 + 4 + SALT_SIZE + NONCE_SIZE;
+
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+/// Upper bound on iterations accepted from a container header, which is read before the
+/// passphrase can be checked: otherwise a forged header makes `open` run for hours.
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+/// Parameters of the key derivation function stored in the container header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KdfParams {
+    pub iterations: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams { iterations: DEFAULT_ITERATIONS }
+    }
+}
+
+/// Stretches the passphrase into encryption and MAC keys, refusing iteration counts `open` can't accept.
+fn derive_keys(passphrase: &[u8], salt: &[u8], params: &KdfParams) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+    if params.iterations == 0 || params.iterations > MAX_ITERATIONS {
+        return Err(AlgorithmError { what: format!("Invalid KDF iteration count: {}", params.iterations) });
+    }
+    let mut enc_key = pbkdf2_hmac_sha256(passphrase, salt, params.iterations, 64);
+    let mac_key = enc_key.split_off(32);
+    Ok((enc_key, mac_key))
+}
+
+/// Encrypts plaintext into a container, using fresh random salt and nonce.
+pub fn seal(passphrase: &[u8], plaintext: &[u8], params: &KdfParams) -> Result<Vec<u8>, AlgorithmError> {
+    let random = utils::random_bytes(SALT_SIZE + NONCE_SIZE)
+        .map_err(|e| AlgorithmError { what: format!("Can't generate salt and nonce: {}", e) })?;
+    let (salt, nonce) = random.split_at(SALT_SIZE);
+
+    seal_with(passphrase, plaintext, params, salt.try_into().unwrap(), nonce.try_into().unwrap())
+}
+
+/// Encrypts plaintext into a container with given salt and nonce. Never reuse them with the same
+/// passphrase; prefer `seal` unless you need reproducible output.
+pub fn seal_with(
+    passphrase: &[u8], plaintext: &[u8], params: &KdfParams,
+    salt: &[u8; SALT_SIZE], nonce: &[u8; NONCE_SIZE],
+) -> Result<Vec<u8>, AlgorithmError> {
+    let (enc_key, mac_key) = derive_keys(passphrase, salt, params)?;
+
+    let mut result = Vec::with_capacity(HEADER_SIZE + plaintext.len() + TAG_SIZE);
+    result.extend_from_slice(MAGIC);
+    result.push(VERSION);
+    result.push(KDF_PBKDF2_HMAC_SHA256);
+    result.extend_from_slice(&params.iterations.to_be_bytes());
+    result.extend_from_slice(salt);
+    result.extend_from_slice(nonce);
+
+    let mut ciphertext = plaintext.to_vec();
+    aes::ctr::apply_keystream(&Aes::new(&enc_key).unwrap(), nonce, &mut ciphertext);
+    result.extend_from_slice(&ciphertext);
+
+    let tag = hmac_sha256(&mac_key, &result);
+    result.extend_from_slice(&tag);
+
+    Ok(result)
+}
+
+/// Decrypts a container, refusing it if the passphrase is wrong or the data was modified.
+pub fn open(passphrase: &[u8], data: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+    let error = |what: &str| Err(AlgorithmError { what: what.to_string() });
+
+    if data.len() < HEADER_SIZE + TAG_SIZE || &data[..4] != MAGIC {
+        return error("Not an encrypted container");
+    }
+    if data[4] != VERSION {
+        return Err(AlgorithmError { what: format!("Unsupported container version: {}", data[4]) });
+    }
+    if data[5] != KDF_PBKDF2_HMAC_SHA256 {
+        return Err(AlgorithmError { what: format!("Unsupported key derivation function: {}", data[5]) });
+    }
+
+    let params = KdfParams { iterations: u32::from_be_bytes(data[6..10].try_into().unwrap()) };
+    let salt = &data[10..10 + SALT_SIZE];
+    let nonce: aes::Block = data[10 + SALT_SIZE..HEADER_SIZE].try_into().unwrap();
+    let (authenticated, tag) = data.split_at(data.len() - TAG_SIZE);
+
+    let (enc_key, mac_key) = derive_keys(passphrase, salt, &params)?;
+    if !utils::constant_time_eq(&hmac_sha256(&mac_key, authenticated), tag) {
+        return error("Wrong passphrase or corrupted container");
+    }
+
+    let mut plaintext = authenticated[HEADER_SIZE..].to_vec();
+    aes::ctr::apply_keystream(&Aes::new(&enc_key).unwrap(), &nonce, &mut plaintext);
+
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: KdfParams = KdfParams { iterations: 10 };
+
+    #[test]
+    pub fn test_seal_open() {
+        let text = b"some secret text";
+        let sealed = seal(b"passphrase", text, &PARAMS).unwrap();
+
+        assert_eq!(sealed.len(), HEADER_SIZE + text.len() + TAG_SIZE);
+        assert_eq!(open(b"passphrase", &sealed).unwrap(), text.to_vec());
+    }
+
+    #[test]
+    pub fn test_wrong_passphrase() {
+        let sealed = seal_with(b"passphrase", b"text", &PARAMS, &[1; SALT_SIZE], &[2; NONCE_SIZE]).unwrap();
+        assert!(open(b"passphrase!", &sealed).is_err());
+    }
+
+    #[test]
+    pub fn test_tampered_container() {
+        let sealed = seal_with(b"passphrase", b"text", &PARAMS, &[1; SALT_SIZE], &[2; NONCE_SIZE]).unwrap();
+
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert!(open(b"passphrase", &tampered).is_err(), "flipped bit in byte {} went unnoticed", i);
+        }
+
+        assert!(open(b"passphrase", &sealed[..sealed.len() - 1]).is_err());
+    }
+
+    #[test]
+    pub fn test_iteration_limit() {
+        let mut sealed = seal_with(b"passphrase", b"text", &PARAMS, &[1; SALT_SIZE], &[2; NONCE_SIZE]).unwrap();
+        sealed[6..10].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(open(b"passphrase", &sealed).unwrap_err().what.contains("iteration count"));
+
+        assert!(seal(b"passphrase", b"text", &KdfParams { iterations: MAX_ITERATIONS + 1 }).is_err());
+        assert!(seal(b"passphrase", b"text", &KdfParams { iterations: 0 }).is_err());
+        for &iterations in [0, MAX_ITERATIONS + 1].iter() {
+            assert!(seal_with(b"passphrase", b"text", &KdfParams { iterations }, &[1; SALT_SIZE], &[2; NONCE_SIZE]).is_err());
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::classical::Alphabet;
+use crate::text_stats::{self, ENGLISH_FREQUENCIES, RUSSIAN_FREQUENCIES};
+use crate::utils::AlgorithmError;
+
+/// Letter frequencies of the text, indexed by position in alphabet. Computed with
+/// `text_stats::frequencies`, so any text can serve as a reference corpus.
+pub fn letter_frequencies(text: &str, alphabet: &Alphabet) -> Vec<f64> {
+    let mut result = vec![0f64; alphabet.len()];
+    for (letter, freq) in text_stats::frequencies(&text.to_lowercase(), |c| alphabet.contains(c), 1) {
+        if let Some(i) = letter.chars().next().and_then(|c| alphabet.index_of(c)) {
+            result[i] += freq;
+        }
+    }
+    result
+}
+
+/// Built-in reference frequencies for the predefined Russian and English alphabets.
+pub fn builtin_frequencies(alphabet: &Alphabet) -> Option<Vec<f64>> {
+    if alphabet.letters() == Alphabet::english().letters() {
+        Some(ENGLISH_FREQUENCIES.to_vec())
+    } else if alphabet.letters() == Alphabet::russian().letters() {
+        Some(RUSSIAN_FREQUENCIES.to_vec())
+    } else {
+        None
+    }
+}
+
+/// Index of coincidence: probability that two letters picked from the text at random are equal.
+pub fn index_of_coincidence(letters: &[usize], alphabet_len: usize) -> f64 {
+    if letters.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts = vec![0u64; alphabet_len];
+    letters.iter().for_each(|&i| counts[i] += 1);
+
+    let n = letters.len() as f64;
+    counts.iter().map(|&c| (c * c.saturating_sub(1)) as f64).sum::<f64>() / (n * (n - 1.0))
+}
+
+/// Breaking Vigenère cipher: the period is estimated with Kasiski examination and index of
+/// coincidence (Friedman test), then every column of the text is a Caesar cipher, which is
+/// solved by minimizing chi-squared statistic against reference letter frequencies.
+pub mod vigenere {
+    use super::*;
+    use std::collections::HashMap;
+
+    const KASISKI_WINDOW: usize = 3;
+
+    /// Recovered key letter along with how sure we are about it.
+    #[derive(Debug, Clone)]
+    pub struct KeyLetter {
+        pub letter: char,
+        /// Chi-squared statistic of the best shift (lower is better)
+        pub chi_squared: f64,
+        /// 1 - best / second best chi-squared: close to 0 means the two best shifts are
+        /// equally plausible, close to 1 means the best shift is a clear winner
+        pub confidence: f64,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Analysis {
+        /// (period, number of repeated trigram distances divisible by it), most common first
+        pub kasiski: Vec<(usize, usize)>,
+        /// Period estimated by Friedman's formula
+        pub friedman: f64,
+        /// (period, average index of coincidence of columns) for every tried period
+        pub column_ic: Vec<(usize, f64)>,
+        pub period: usize,
+        pub key: Vec<KeyLetter>,
+    }
+
+    impl Analysis {
+        pub fn key_string(&self) -> String {
+            self.key.iter().map(|k| k.letter).collect()
+        }
+    }
+
+    /// Counts distances between repeated trigrams which are divisible by each period in 2..=max_period.
+    pub fn kasiski(letters: &[usize], max_period: usize) -> Vec<(usize, usize)> {
+        let mut positions: HashMap<&[usize], Vec<usize>> = HashMap::new();
+        for (i, window) in letters.windows(KASISKI_WINDOW).enumerate() {
+            positions.entry(window).or_default().push(i);
+        }
+
+        let distances = positions
+            .values()
+            .flat_map(|pos| pos.windows(2).map(|w| w[1] - w[0]))
+            .collect::<Vec<usize>>();
+
+        let mut result = (2..=max_period)
+            .map(|p| (p, distances.iter().filter(|d| d.is_multiple_of(p)).count()))
+            .collect::<Vec<(usize, usize)>>();
+        result.sort_by(|(p1, c1), (p2, c2)| c2.cmp(c1).then(p1.cmp(p2)));
+        result
+    }
+
+    /// Friedman's estimate of the period: (Kp - Kr) / (Ko - Kr), where Kp is the index of
+    /// coincidence of the language, Kr of uniformly random text and Ko of the ciphertext.
+    pub fn friedman(letters: &[usize], reference: &[f64]) -> f64 {
+        let kp = reference.iter().map(|p| p * p).sum::<f64>();
+        let kr = 1.0 / reference.len() as f64;
+        let ko = index_of_coincidence(letters, reference.len());
+        (kp - kr) / (ko - kr)
+    }
+
+    fn column(letters: &[usize], period: usize, offset: usize) -> Vec<usize> {
+        letters.iter().skip(offset).step_by(period).copied().collect()
+    }
+
+    /// Average index of coincidence of columns when text is split into given number of columns.
+    pub fn column_ic(letters: &[usize], period: usize, alphabet_len: usize) -> f64 {
+        (0..period)
+            .map(|offset| index_of_coincidence(&column(letters, period, offset), alphabet_len))
+            .sum::<f64>() / period as f64
+    }
+
+    /// Chi-squared statistic of column letters shifted back by `shift` against reference frequencies.
+    pub fn chi_squared(column: &[usize], shift: usize, reference: &[f64]) -> f64 {
+        let n = reference.len();
+        let mut counts = vec![0f64; n];
+        column.iter().for_each(|&i| counts[(i + n - shift) % n] += 1.0);
+
+        counts
+            .iter()
+            .zip(reference.iter())
+            .map(|(observed, p)| {
+                // letters which never occur in reference would give division by zero
+                let expected = p.max(1e-4) * column.len() as f64;
+                (observed - expected).powi(2) / expected
+            })
+            .sum()
+    }
+
+    fn solve_column(column: &[usize], alphabet: &Alphabet, reference: &[f64]) -> KeyLetter {
+        let mut scores = (0..alphabet.len())
+            .map(|shift| (shift, chi_squared(column, shift, reference)))
+            .collect::<Vec<(usize, f64)>>();
+        scores.sort_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap());
+
+        let (shift, best) = scores[0];
+        let (_, second) = scores[1];
+        KeyLetter {
+            letter: alphabet.letter(shift),
+            chi_squared: best,
+            confidence: if second > 0.0 { 1.0 - best / second } else { 0.0 },
+        }
+    }
+
+    /// Recovers Vigenère key of the text. `reference` is letter frequencies of the language,
+    /// indexed by position in alphabet.
+    pub fn analyze(text: &str, alphabet: &Alphabet, reference: &[f64], max_period: usize)
+        -> Result<Analysis, AlgorithmError>
+    {
+        if reference.len() != alphabet.len() {
+            return Err(AlgorithmError {
+                what: format!("Reference has {} letters, alphabet has {}", reference.len(), alphabet.len())
+            });
+        }
+
+        if max_period == 0 {
+            return Err(AlgorithmError { what: "Maximum period must be at least 1".to_string() });
+        }
+
+        let letters = text.chars().filter_map(|c| alphabet.index_of(c)).collect::<Vec<usize>>();
+        if letters.len() < 2 * max_period {
+            return Err(AlgorithmError {
+                what: format!("Text of {} letters is too short to analyze periods up to {}", letters.len(), max_period)
+            });
+        }
+
+        let column_ic = (1..=max_period)
+            .map(|p| (p, column_ic(&letters, p, alphabet.len())))
+            .collect::<Vec<(usize, f64)>>();
+        let kasiski = kasiski(&letters, max_period);
+        let friedman = friedman(&letters, reference);
+
+        // multiples of the true period score as well as the period itself by IC, so every
+        // period close enough to the best one is a candidate. Of those, repeated trigram
+        // distances are divisible by the true period more often than by its multiples; ties
+        // go to the period nearest to Friedman's estimate, then to the smallest one.
+        let kasiski_support = |p: usize| match p {
+            // every distance is divisible by 1
+            1 => usize::MAX,
+            _ => kasiski.iter().find(|&&(q, _)| q == p).map_or(0, |&(_, count)| count),
+        };
+        let friedman_distance = |p: usize| {
+            let d = (p as f64 - friedman).abs();
+            if d.is_nan() { f64::INFINITY } else { d }
+        };
+
+        let best_ic = column_ic.iter().map(|&(_, ic)| ic).fold(0.0, f64::max);
+        let period = column_ic
+            .iter()
+            .filter(|&&(_, ic)| ic >= 0.9 * best_ic)
+            .map(|&(p, _)| p)
+            .min_by(|&p, &q| kasiski_support(q).cmp(&kasiski_support(p))
+                .then(friedman_distance(p).partial_cmp(&friedman_distance(q)).unwrap())
+                .then(p.cmp(&q)))
+            .unwrap();
+
+        let key = (0..period)
+            .map(|offset| solve_column(&column(&letters, period, offset), alphabet, reference))
+            .collect();
+
+        Ok(Analysis {
+            kasiski,
+            friedman,
+            column_ic,
+            period,
+            key,
+        })
+    }
+}
+
+/// Solving simple substitution cipher by maximizing the n-gram log-probability of the
+/// decrypted text. Search is hill climbing over key swaps with simulated annealing
+/// (worse keys are sometimes accepted while the temperature is high) and random restarts.
+pub mod substitution {
+    use super::*;
+    use crate::classical::substitution::invert;
+    use crate::utils::Xorshift64;
+    use std::collections::HashMap;
+
+    /// Log-probabilities of all n-grams of the alphabet, estimated from a reference corpus.
+    pub struct NgramModel {
+        n: usize,
+        alphabet_len: usize,
+        log_probs: Vec<f64>,
+    }
+
+    impl NgramModel {
+
+        /// Builds the model with `text_stats::counts`. N-grams crossing word boundaries are skipped.
+        /// N-grams which do not occur in corpus get probability of 0.01 occurrences.
+        pub fn from_corpus(corpus: &str, alphabet: &Alphabet, n: usize) -> Result<NgramModel, AlgorithmError> {
+            if n == 0 || alphabet.len().checked_pow(n as u32).is_none_or(|size| size > 1 << 24) {
+                return Err(AlgorithmError { what: format!("Unsupported n-gram size: {}", n) });
+            }
+
+            let corpus = corpus.to_lowercase();
+            let (total, counts) = text_stats::counts(&corpus, |c| alphabet.contains(c), n);
+            if total == 0 {
+                return Err(AlgorithmError { what: "Reference corpus contains no n-grams".to_string() });
+            }
+
+            let floor = (0.01 / total as f64).log10();
+            let mut log_probs = vec![floor; alphabet.len().pow(n as u32)];
+            for (ngram, count) in counts {
+                let letters = ngram.chars().filter_map(|c| alphabet.index_of(c)).collect::<Vec<usize>>();
+                log_probs[ngram_index(&letters, alphabet.len())] = (count as f64 / total as f64).log10();
+            }
+
+            Ok(NgramModel { n, alphabet_len: alphabet.len(), log_probs })
+        }
+
+        pub fn n(&self) -> usize {
+            self.n
+        }
+    }
+
+    fn ngram_index(letters: &[usize], alphabet_len: usize) -> usize {
+        letters.iter().fold(0, |acc, &l| acc * alphabet_len + l)
+    }
+
+    /// Parameters of the search. Same seed always gives the same result.
+    #[derive(Debug, Clone)]
+    pub struct SolverParams {
+        pub seed: u64,
+        pub restarts: usize,
+        pub iterations: usize,
+        /// Initial temperature, decreases linearly to zero during each restart. Measured in the
+        /// same units as the score, so keep it well below typical per-n-gram log-probabilities
+        pub temperature: f64,
+    }
+
+    impl Default for SolverParams {
+        fn default() -> Self {
+            SolverParams { seed: 0, restarts: 10, iterations: 10_000, temperature: 0.005 }
+        }
+    }
+
+    /// Reported after every restart.
+    #[derive(Debug, Clone)]
+    pub struct Progress {
+        pub restart: usize,
+        pub score: f64,
+        pub best_score: f64,
+        pub best_key: String,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Solution {
+        /// Encryption key in the format of `classical::substitution`: i-th letter of the key
+        /// is the cipher letter for i-th letter of the alphabet. Letters which do not occur in
+        /// the text are placed arbitrarily.
+        pub key: String,
+        /// Average log10-probability per n-gram of the decrypted text
+        pub score: f64,
+    }
+
+    /// Ciphertext n-grams with their counts; decryption key is applied to them on every scoring
+    struct CipherNgrams {
+        ngrams: Vec<(Vec<usize>, f64)>,
+        total: f64,
+    }
+
+    impl CipherNgrams {
+        fn new(text: &str, alphabet: &Alphabet, n: usize) -> CipherNgrams {
+            let text = text.to_lowercase();
+            let (total, counts) = text_stats::counts(&text, |c| alphabet.contains(c), n);
+            let mut ngrams = counts
+                .into_iter()
+                .map(|(ngram, count)| (ngram.chars().filter_map(|c| alphabet.index_of(c)).collect(), count as f64))
+                .collect::<Vec<(Vec<usize>, f64)>>();
+            // HashMap iteration order is random, but float sums depend on the order
+            ngrams.sort_by(|(x, _), (y, _)| x.cmp(y));
+            CipherNgrams { ngrams, total: total as f64 }
+        }
+
+        fn score(&self, model: &NgramModel, decryption: &[usize]) -> f64 {
+            self.ngrams
+                .iter()
+                .map(|(ngram, count)| {
+                    let index = ngram.iter().fold(0, |acc, &l| acc * model.alphabet_len + decryption[l]);
+                    count * model.log_probs[index]
+                })
+                .sum::<f64>() / self.total
+        }
+    }
+
+    /// Key which maps cipher letters to plaintext letters of the same frequency rank
+    fn frequency_key(text: &str, alphabet: &Alphabet, reference: &[f64]) -> Vec<usize> {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        text.chars().filter_map(|c| alphabet.index_of(c)).for_each(|i| *counts.entry(i).or_insert(0) += 1);
+
+        let mut cipher_order = (0..alphabet.len()).collect::<Vec<usize>>();
+        cipher_order.sort_by_key(|i| (std::cmp::Reverse(counts.get(i).copied().unwrap_or(0)), *i));
+
+        let mut plain_order = (0..alphabet.len()).collect::<Vec<usize>>();
+        plain_order.sort_by(|&i, &j| reference[j].partial_cmp(&reference[i]).unwrap().then(i.cmp(&j)));
+
+        let mut decryption = vec![0; alphabet.len()];
+        cipher_order.iter().zip(plain_order.iter()).for_each(|(&c, &p)| decryption[c] = p);
+        decryption
+    }
+
+    fn key_string(decryption: &[usize], alphabet: &Alphabet) -> String {
+        invert(decryption).iter().map(|&i| alphabet.letter(i)).collect()
+    }
+
+    /// Recovers the key of the substitution cipher. `reference` is letter frequencies of the
+    /// language, used for the initial guess; `progress` is called after every restart.
+    pub fn solve<F>(
+        text: &str, alphabet: &Alphabet, model: &NgramModel, reference: &[f64],
+        params: &SolverParams, mut progress: F,
+    ) -> Result<Solution, AlgorithmError>
+        where F: FnMut(&Progress)
+    {
+        if model.alphabet_len != alphabet.len() || reference.len() != alphabet.len() {
+            return Err(AlgorithmError { what: "Model and reference must match the alphabet".to_string() });
+        }
+
+        let cipher = CipherNgrams::new(text, alphabet, model.n);
+        if cipher.ngrams.is_empty() {
+            return Err(AlgorithmError { what: "Text contains no n-grams to score".to_string() });
+        }
+
+        let mut rng = Xorshift64::new(params.seed);
+        let n = alphabet.len();
+
+        let mut best_key = frequency_key(text, alphabet, reference);
+        let mut best_score = cipher.score(model, &best_key);
+
+        for restart in 0..params.restarts {
+            let mut key = best_key.clone();
+            // shake the best key up a bit, except for the first run which starts from it as is
+            for _ in 0..if restart == 0 { 0 } else { n / 3 } {
+                key.swap(rng.below(n), rng.below(n));
+            }
+            let mut score = cipher.score(model, &key);
+
+            for iteration in 0..params.iterations {
+                let temperature = params.temperature * (1.0 - iteration as f64 / params.iterations as f64);
+
+                let (i, j) = (rng.below(n), rng.below(n));
+                if i == j {
+                    continue;
+                }
+                key.swap(i, j);
+
+                let new_score = cipher.score(model, &key);
+                let delta = new_score - score;
+                if delta >= 0.0 || (temperature > 0.0 && rng.next_f64() < (delta / temperature).exp()) {
+                    score = new_score;
+                } else {
+                    key.swap(i, j);
+                }
+
+                if score > best_score {
+                    best_score = score;
+                    best_key = key.clone();
+                }
+            }
+
+            progress(&Progress { restart, score, best_score, best_key: key_string(&best_key, alphabet) });
+        }
+
+        Ok(Solution { key: key_string(&best_key, alphabet), score: best_score })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classical;
+
+    pub const ENGLISH_TEXT: &str = "\
+        It was the best of times, it was the worst of times, it was the age of wisdom, it was the \
+        age of foolishness, it was the epoch of belief, it was the epoch of incredulity, it was the \
+        season of Light, it was the season of Darkness, it was the spring of hope, it was the winter \
+        of despair, we had everything before us, we had nothing before us, we were all going direct \
+        to Heaven, we were all going direct the other way - in short, the period was so far like the \
+        present period, that some of its noisiest authorities insisted on its being received, for good \
+        or for evil, in the superlative degree of comparison only. There were a king with a large jaw \
+        and a queen with a plain face, on the throne of England; there were a king with a large jaw and \
+        a queen with a fair face, on the throne of France. In both countries it was clearer than crystal \
+        to the lords of the State preserves of loaves and fishes, that things in general were settled \
+        for ever.";
+
+    pub const RUSSIAN_TEXT: &str = "\
+        Мой дядя самых честных правил, когда не в шутку занемог, он уважать себя заставил и лучше \
+        выдумать не мог. Его пример другим наука; но, боже мой, какая скука с больным сидеть и день и \
+        ночь, не отходя ни шагу прочь! Какое низкое коварство полуживого забавлять, ему подушки \
+        поправлять, печально подносить лекарство, вздыхать и думать про себя: когда же черт возьмет \
+        тебя! Так думал молодой повеса, летя в пыли на почтовых, всевышней волею Зевеса наследник всех \
+        своих родных. Друзья Людмилы и Руслана! С героем моего романа без предисловий, сей же час \
+        позвольте познакомить вас: Онегин, добрый мой приятель, родился на брегах Невы, где, может быть, \
+        родились вы или блистали, мой читатель; там некогда гулял и я: но вреден север для меня.";
+
+    /// Training corpora for n-gram models, independent of the texts the tests decrypt
+    const ENGLISH_CORPUS: &str = "\
+        It is a truth universally acknowledged, that a single man in possession of a good fortune, \
+        must be in want of a wife. However little known the feelings or views of such a man may be on \
+        his first entering a neighbourhood, this truth is so well fixed in the minds of the surrounding \
+        families, that he is considered the rightful property of some one or other of their daughters. \
+        Call me Ishmael. Some years ago, never mind how long precisely, having little or no money in my \
+        purse, and nothing particular to interest me on shore, I thought I would sail about a little and \
+        see the watery part of the world. It is a way I have of driving off the spleen and regulating the \
+        circulation. Whenever I find myself growing grim about the mouth; whenever it is a damp, drizzly \
+        November in my soul; whenever I find myself involuntarily pausing before coffin warehouses, and \
+        bringing up the rear of every funeral I meet; then, I account it high time to get to sea as soon \
+        as I can. Four score and seven years ago our fathers brought forth on this continent, a new \
+        nation, conceived in Liberty, and dedicated to the proposition that all men are created equal. \
+        Now we are engaged in a great civil war, testing whether that nation, or any nation so conceived \
+        and so dedicated, can long endure. We are met on a great battle-field of that war. We have come \
+        to dedicate a portion of that field, as a final resting place for those who here gave their \
+        lives that that nation might live. It is altogether fitting and proper that we should do this. \
+        Happy families are all alike; every unhappy family is unhappy in its own way. Everything was in \
+        confusion in the house. The wife had discovered that the husband was carrying on an intrigue \
+        with a French girl, who had been a governess in their family, and she had announced to her \
+        husband that she could not go on living in the same house with him. This position of affairs had \
+        now lasted three days, and not only the husband and wife themselves, but all the members of \
+        their family and household, were painfully conscious of it. Every person in the house felt that \
+        there was so sense in their living together, and that the stray people brought together by \
+        chance in any inn had more in common with one another than they, the members of the family and \
+        household. The quick brown fox jumps over the lazy dog while the zebra and the jackal quietly \
+        watch from the hazy ridge, and the sixth sheikh's sixth sheep is sick.";
+
+    const RUSSIAN_CORPUS: &str = "\
+        Все счастливые семьи похожи друг на друга, каждая несчастливая семья несчастлива по-своему. \
+        Все смешалось в доме Облонских. Жена узнала, что муж был в связи с бывшею в их доме \
+        француженкою-гувернанткой, и объявила мужу, что не может жить с ним в одном доме. Положение \
+        это продолжалось уже третий день и мучительно чувствовалось и самими супругами, и всеми членами \
+        семьи, и домочадцами. Все члены семьи и домочадцы чувствовали, что нет смысла в их \
+        сожительстве и что на каждом постоялом дворе случайно сошедшиеся люди более связаны между \
+        собой, чем они, члены семьи и домочадцы Облонских. Жена не выходила из своих комнат, мужа третий \
+        день не было дома. Дети бегали по всему дому, как потерянные; англичанка поссорилась с \
+        экономкой и написала записку приятельнице, прося приискать ей новое место; повар ушел вчера со \
+        двора, во время самого обеда; черная кухарка и кучер просили расчета. В начале июля, в \
+        чрезвычайно жаркое время, под вечер, один молодой человек вышел из своей каморки, которую \
+        нанимал от жильцов в переулке, на улицу и медленно, как бы в нерешимости, отправился к мосту. \
+        Он благополучно избегнул встречи с своею хозяйкой на лестнице. Каморка его приходилась под \
+        самою кровлей высокого пятиэтажного дома и походила более на шкаф, чем на квартиру. Квартирная \
+        же хозяйка его, у которой он нанимал эту каморку с обедом и прислугой, помещалась одною \
+        лестницей ниже, в отдельной квартире, и каждый раз, при выходе на улицу, ему непременно надо \
+        было проходить мимо хозяйкиной кухни, почти всегда настежь отворенной на лестницу. Съешь же ещё \
+        этих мягких французских булок да выпей чаю. Широкая электрификация южных губерний даст мощный \
+        толчок подъёму сельского хозяйства. Эх, чужд кайф, сплющь объём вши, грызя цент. \
+        Белеет парус одинокий в тумане моря голубом. Что ищет он в стране далекой? Что кинул он в краю \
+        родном? Играют волны, ветер свищет, и мачта гнется и скрипит; увы, он счастия не ищет и не от \
+        счастия бежит! Под ним струя светлей лазури, над ним луч солнца золотой, а он, мятежный, просит \
+        бури, как будто в бурях есть покой. Я помню чудное мгновенье: передо мной явилась ты, как \
+        мимолетное виденье, как гений чистой красоты. В томленьях грусти безнадежной, в тревогах шумной \
+        суеты, звучал мне долго голос нежный и снились милые черты. Шли годы. Бурь порыв мятежный \
+        рассеял прежние мечты, и я забыл твой голос нежный, твои небесные черты. Мы все учились \
+        понемногу чему-нибудь и как-нибудь, так воспитаньем, слава богу, у нас немудрено блеснуть. \
+        Зима. Крестьянин, торжествуя, на дровнях обновляет путь; его лошадка, снег почуя, плетется \
+        рысью как-нибудь; бразды пушистые взрывая, летит кибитка удалая; ямщик сидит на облучке в \
+        тулупе, в красном кушаке. Мороз и солнце; день чудесный! Еще ты дремлешь, друг прелестный, пора, \
+        красавица, проснись: открой сомкнуты негой взоры навстречу северной Авроры, звездою севера \
+        явись! Вечор, ты помнишь, вьюга злилась, на мутном небе мгла носилась; луна, как бледное пятно, \
+        сквозь тучи мрачные желтела, и ты печальная сидела, а нынче погляди в окно: под голубыми \
+        небесами великолепными коврами, блестя на солнце, снег лежит; прозрачный лес один чернеет, и \
+        ель сквозь иней зеленеет, и речка подо льдом блестит. Я вас любил: любовь еще, быть может, в \
+        душе моей угасла не совсем; но пусть она вас больше не тревожит; я не хочу печалить вас ничем. \
+        Буря мглою небо кроет, вихри снежные крутя; то, как зверь, она завоет, то заплачет, как дитя, \
+        то по кровле обветшалой вдруг соломой зашумит, то, как путник запоздалый, к нам в окошко \
+        застучит. Наша ветхая лачужка и печальна и темна. Что же ты, моя старушка, приумолкла у окна?";
+
+    #[test]
+    pub fn test_index_of_coincidence() {
+        let en = Alphabet::english();
+        let letters = ENGLISH_TEXT.chars().filter_map(|c| en.index_of(c)).collect::<Vec<usize>>();
+        let ic = index_of_coincidence(&letters, en.len());
+        assert!(ic > 0.055 && ic < 0.075, "IC of English text is {}", ic);
+    }
+
+    #[test]
+    pub fn test_break_english() {
+        let en = Alphabet::english();
+        let encrypted = classical::vigenere::encrypt(ENGLISH_TEXT, &en, "lemon").unwrap();
+
+        let analysis = vigenere::analyze(&encrypted, &en, &ENGLISH_FREQUENCIES, 12).unwrap();
+
+        assert_eq!(analysis.period, 5);
+        assert_eq!(analysis.kasiski[0].0, 5);
+        assert_eq!(analysis.key_string(), "lemon");
+    }
+
+    #[test]
+    pub fn test_break_russian() {
+        let ru = Alphabet::russian();
+        let encrypted = classical::vigenere::encrypt(RUSSIAN_TEXT, &ru, "шифр").unwrap();
+
+        let reference = builtin_frequencies(&ru).unwrap();
+        let analysis = vigenere::analyze(&encrypted, &ru, &reference, 10).unwrap();
+
+        assert_eq!(analysis.period, 4);
+        assert_eq!(analysis.key_string(), "шифр");
+    }
+
+    #[test]
+    pub fn test_reference_from_corpus() {
+        let en = Alphabet::english();
+        let reference = letter_frequencies(ENGLISH_TEXT, &en);
+        assert!((reference.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(reference.len(), en.len());
+
+        let encrypted = classical::vigenere::encrypt(ENGLISH_TEXT, &en, "key").unwrap();
+        assert_eq!(vigenere::analyze(&encrypted, &en, &reference, 8).unwrap().key_string(), "key");
+    }
+
+    #[test]
+    pub fn test_period_choice() {
+        let en = Alphabet::english();
+
+        // a multiple of the period scores as well by IC, Kasiski picks the period itself
+        let encrypted = classical::vigenere::encrypt(ENGLISH_TEXT, &en, "key").unwrap();
+        let analysis = vigenere::analyze(&encrypted, &en, &ENGLISH_FREQUENCIES, 12).unwrap();
+        assert!(analysis.column_ic.iter().any(|&(p, ic)| p == 6 && ic >= 0.9 * analysis.column_ic[2].1));
+        assert_eq!(analysis.period, 3);
+
+        let caesar = classical::vigenere::encrypt(ENGLISH_TEXT, &en, "d").unwrap();
+        assert_eq!(vigenere::analyze(&caesar, &en, &ENGLISH_FREQUENCIES, 12).unwrap().period, 1);
+
+        assert!(vigenere::analyze(&encrypted, &en, &ENGLISH_FREQUENCIES, 0).is_err());
+    }
+
+    fn solve_substitution(text: &str, corpus: &str, alphabet: &Alphabet, key: &str, seed: u64) -> substitution::Solution {
+        let encrypted = classical::substitution::encrypt(text, alphabet, key).unwrap();
+
+        let model = substitution::NgramModel::from_corpus(corpus, alphabet, 3).unwrap();
+        let reference = builtin_frequencies(alphabet).unwrap();
+        let params = substitution::SolverParams { seed, restarts: 8, iterations: 10000, ..Default::default() };
+
+        let solution = substitution::solve(&encrypted, alphabet, &model, &reference, &params, |_| {}).unwrap();
+
+        // letters which occur once or twice can't be told apart by a model trained on other
+        // text, so a few of them may stay swapped
+        let decrypted = classical::substitution::decrypt(&encrypted, alphabet, &solution.key).unwrap();
+        let letters = text.chars().filter(|&c| alphabet.contains(c.to_lowercase().next().unwrap())).count();
+        let wrong = decrypted.chars().zip(text.chars()).filter(|(d, t)| d != t).count();
+        assert!(wrong * 50 <= letters, "{} of {} letters are wrong:\n{}", wrong, letters, decrypted);
+        solution
+    }
+
+    #[test]
+    pub fn test_solve_substitution_english() {
+        solve_substitution(ENGLISH_TEXT, ENGLISH_CORPUS, &Alphabet::english(), "qwertyuiopasdfghjklzxcvbnm", 1);
+    }
+
+    #[test]
+    pub fn test_solve_substitution_russian() {
+        solve_substitution(RUSSIAN_TEXT, RUSSIAN_CORPUS, &Alphabet::russian(), "йцукенгшщзхъфывапролджэячсмитьбюё", 2);
+    }
+
+    #[test]
+    pub fn test_solve_substitution_reproducible() {
+        let en = Alphabet::english();
+        let key = "zyxwvutsrqponmlkjihgfedcba";
+        assert_eq!(solve_substitution(ENGLISH_TEXT, ENGLISH_CORPUS, &en, key, 42).key, solve_substitution(ENGLISH_TEXT, ENGLISH_CORPUS, &en, key, 42).key);
+    }
+}
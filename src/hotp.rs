@@ -0,0 +1,269 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::checksum::hmac::{hmac_sha1, hmac_sha256, hmac_sha512};
+use crate::codec::{base32, Strictness};
+use crate::utils::{self, AlgorithmError};
+
+pub const DEFAULT_DIGITS: u32 = 6;
+
+pub const DEFAULT_PERIOD: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    fn hmac(&self, key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Sha1 => hmac_sha1(key, data).to_vec(),
+            Algorithm::Sha256 => hmac_sha256(key, data).to_vec(),
+            Algorithm::Sha512 => hmac_sha512(key, data).to_vec(),
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().replace('-', "").as_str() {
+            "SHA1" => Ok(Algorithm::Sha1),
+            "SHA256" => Ok(Algorithm::Sha256),
+            "SHA512" => Ok(Algorithm::Sha512),
+            _ => Err(AlgorithmError { what: format!("Unknown HMAC algorithm: {} (known: SHA1, SHA256, SHA512)", s) }),
+        }
+    }
+}
+
+/// Counter-based one-time passwords (RFC 4226).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hotp {
+    pub secret: Vec<u8>,
+    pub algorithm: Algorithm,
+    pub digits: u32,
+}
+
+impl Hotp {
+    pub fn new(secret: &[u8], algorithm: Algorithm, digits: u32) -> Result<Hotp, AlgorithmError> {
+        if !(6..=9).contains(&digits) {
+            return Err(AlgorithmError { what: format!("Code must have 6 to 9 digits, got {}", digits) });
+        }
+        Ok(Hotp { secret: secret.to_vec(), algorithm, digits })
+    }
+
+    /// Code for the counter, zero-padded to `digits`
+    pub fn generate(&self, counter: u64) -> String {
+        let mac = self.algorithm.hmac(&self.secret, &counter.to_be_bytes());
+        // dynamic truncation: low nibble of the last byte selects four bytes of the MAC
+        let offset = (mac[mac.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([mac[offset] & 0x7f, mac[offset + 1], mac[offset + 2], mac[offset + 3]]);
+        format!("{:0width$}", binary % 10u32.pow(self.digits), width = self.digits as usize)
+    }
+
+    /// Checks the code against counters `counter..=counter + look_ahead`, returning the one that
+    /// matched, so that the caller can move its counter past it.
+    pub fn verify(&self, code: &str, counter: u64, look_ahead: u64) -> Option<u64> {
+        (counter..=counter.saturating_add(look_ahead))
+            .find(|&c| utils::constant_time_eq(self.generate(c).as_bytes(), code.trim().as_bytes()))
+    }
+}
+
+/// Time-based one-time passwords (RFC 6238): HOTP with the number of `period`s since `t0` as counter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Totp {
+    pub hotp: Hotp,
+    pub period: u64,
+    pub t0: u64,
+}
+
+impl Totp {
+    pub fn new(hotp: Hotp, period: u64) -> Result<Totp, AlgorithmError> {
+        if period == 0 {
+            return Err(AlgorithmError { what: "TOTP period must be positive".to_string() });
+        }
+        Ok(Totp { hotp, period, t0: 0 })
+    }
+
+    pub fn counter(&self, unix_time: u64) -> u64 {
+        unix_time.saturating_sub(self.t0) / self.period
+    }
+
+    pub fn generate(&self, unix_time: u64) -> String {
+        self.hotp.generate(self.counter(unix_time))
+    }
+
+    /// Checks the code against `window` time steps before and after the current one, to allow
+    /// for clock drift. Returns the offset in steps of the matching code.
+    pub fn verify(&self, code: &str, unix_time: u64, window: u64) -> Option<i64> {
+        let current = self.counter(unix_time);
+        let first = current.saturating_sub(window);
+        self.hotp
+            .verify(code, first, current.saturating_add(window) - first)
+            .map(|c| c.wrapping_sub(current) as i64)
+    }
+}
+
+/// Current Unix time in seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Hotp { generator: Hotp, counter: u64 },
+    Totp(Totp),
+}
+
+/// Key URI as exported by authenticator apps:
+/// `otpauth://totp/Issuer:account?secret=BASE32&issuer=Issuer&algorithm=SHA1&digits=6&period=30`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtpAuthUri {
+    pub label: String,
+    pub issuer: Option<String>,
+    pub kind: Kind,
+}
+
+fn percent_decode(s: &str) -> Result<String, AlgorithmError> {
+    let invalid = || AlgorithmError { what: format!("Invalid percent-encoding: {}", s) };
+
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'%' => {
+                let hex = [iter.next().ok_or_else(invalid)?, iter.next().ok_or_else(invalid)?];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            },
+            b'+' => bytes.push(b' '),
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+impl FromStr for OtpAuthUri {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |what: String| AlgorithmError { what };
+
+        let rest = s.trim().strip_prefix("otpauth://").ok_or_else(|| error("Not an otpauth:// URI".to_string()))?;
+        let (kind, rest) = rest.split_once('/').ok_or_else(|| error("otpauth URI has no label".to_string()))?;
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let (mut secret, mut issuer, mut counter) = (None, None, None);
+        let (mut algorithm, mut digits, mut period) = (Algorithm::Sha1, DEFAULT_DIGITS, DEFAULT_PERIOD);
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value)?;
+            match key.to_lowercase().as_str() {
+                "secret" => secret = Some(base32::decode(&value, Strictness::Lenient)?),
+                "issuer" => issuer = Some(value),
+                "algorithm" => algorithm = value.parse()?,
+                "digits" => digits = value.parse().map_err(|_| error(format!("Invalid digits: {}", value)))?,
+                "period" => period = value.parse().map_err(|_| error(format!("Invalid period: {}", value)))?,
+                "counter" => counter = Some(value.parse().map_err(|_| error(format!("Invalid counter: {}", value)))?),
+                // unknown parameters, like `image`, are ignored as apps do
+                _ => {},
+            }
+        }
+
+        let secret = secret.ok_or_else(|| error("otpauth URI has no secret".to_string()))?;
+        let generator = Hotp::new(&secret, algorithm, digits)?;
+        let kind = match kind.to_lowercase().as_str() {
+            "totp" => Kind::Totp(Totp::new(generator, period)?),
+            "hotp" => Kind::Hotp {
+                generator,
+                counter: counter.ok_or_else(|| error("HOTP URI has no counter".to_string()))?,
+            },
+            other => return Err(error(format!("Unknown OTP type: {}", other))),
+        };
+
+        Ok(OtpAuthUri { label: percent_decode(label)?, issuer, kind })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4226, appendix D
+    #[test]
+    pub fn test_hotp_rfc4226() {
+        let hotp = Hotp::new(b"12345678901234567890", Algorithm::Sha1, 6).unwrap();
+        let expected = ["755224", "287082", "359152", "969429", "338314",
+                        "254676", "287922", "162583", "399871", "520489"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp.generate(counter as u64), *code);
+        }
+
+        assert_eq!(hotp.verify("969429", 1, 5), Some(3));
+        assert_eq!(hotp.verify("969429", 4, 5), None);
+    }
+
+    // RFC 6238, appendix B
+    #[test]
+    pub fn test_totp_rfc6238() {
+        let seed = b"1234567890123456789012345678901234567890123456789012345678901234";
+        let totps = [(Algorithm::Sha1, 20), (Algorithm::Sha256, 32), (Algorithm::Sha512, 64)]
+            .iter()
+            .map(|&(algorithm, len)| Totp::new(Hotp::new(&seed[..len], algorithm, 8).unwrap(), 30).unwrap())
+            .collect::<Vec<_>>();
+
+        let expected: [(u64, [&str; 3]); 6] = [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (1111111111, ["14050471", "67062674", "99943326"]),
+            (1234567890, ["89005924", "91819424", "93441116"]),
+            (2000000000, ["69279037", "90698825", "38618901"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ];
+        for (time, codes) in expected.iter() {
+            for (totp, code) in totps.iter().zip(codes.iter()) {
+                assert_eq!(totp.generate(*time), *code);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_totp_window() {
+        let totp = Totp::new(Hotp::new(b"12345678901234567890", Algorithm::Sha1, 8).unwrap(), 30).unwrap();
+        assert_eq!(totp.verify("07081804", 1111111109, 0), Some(0));
+        assert_eq!(totp.verify("07081804", 1111111109 + 30, 1), Some(-1));
+        assert_eq!(totp.verify("07081804", 1111111109 - 30, 1), Some(1));
+        assert_eq!(totp.verify("07081804", 1111111109 + 60, 1), None);
+        assert_eq!(totp.verify("94287082", 0, 2), Some(1));
+
+        let totp = Totp::new(totp.hotp, 1).unwrap();
+        assert_eq!(totp.verify(&totp.generate(u64::MAX), u64::MAX, 2), Some(0));
+        assert_eq!(totp.verify(&totp.generate(u64::MAX - 1), u64::MAX, 1), Some(-1));
+        assert_eq!(totp.verify(&totp.generate(1 << 63), (1 << 63) - 1, 1), Some(1));
+    }
+
+    #[test]
+    pub fn test_otpauth_uri() {
+        let uri: OtpAuthUri = "otpauth://totp/ACME%20Co:john@example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+                               &issuer=ACME%20Co&algorithm=SHA1&digits=8&period=30".parse().unwrap();
+        assert_eq!(uri.label, "ACME Co:john@example.com");
+        assert_eq!(uri.issuer.as_deref(), Some("ACME Co"));
+        match uri.kind {
+            Kind::Totp(totp) => assert_eq!(totp.generate(59), "94287082"),
+            other => panic!("expected TOTP, got {:?}", other),
+        }
+
+        let uri: OtpAuthUri = "otpauth://hotp/test?secret=gezdgnbvgy3tqojqgezdgnbvgy3tqojq&counter=3".parse().unwrap();
+        match uri.kind {
+            Kind::Hotp { generator, counter } => assert_eq!(generator.generate(counter), "969429"),
+            other => panic!("expected HOTP, got {:?}", other),
+        }
+
+        assert!("otpauth://hotp/test?secret=GEZDGNBV".parse::<OtpAuthUri>().is_err());
+        assert!("otpauth://totp/test?digits=6".parse::<OtpAuthUri>().is_err());
+        assert!("https://example.com".parse::<OtpAuthUri>().is_err());
+    }
+}
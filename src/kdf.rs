@@ -0,0 +1,99 @@
+use crate::checksum::hmac::hmac_sha256;
+
+/// PBKDF2 (RFC 8018) with HMAC-SHA256 as pseudorandom function.
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, key_len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(key_len);
+
+    for block_idx in 1..=key_len.div_ceil(32) as u32 {
+        let mut u = salt.to_vec();
+        u.extend_from_slice(&block_idx.to_be_bytes());
+
+        let mut u = hmac_sha256(password, &u);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            t.iter_mut().zip(u.iter()).for_each(|(t, u)| *t ^= u);
+        }
+
+        key.extend_from_slice(&t);
+    }
+
+    key.truncate(key_len);
+    key
+}
+
+/// HKDF-Extract (RFC 5869): concentrates entropy of the input keying material into a pseudorandom key.
+pub fn hkdf_extract_sha256(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    hmac_sha256(salt, ikm)
+}
+
+/// HKDF-Expand (RFC 5869): stretches a pseudorandom key into `key_len` bytes bound to `info`.
+pub fn hkdf_expand_sha256(prk: &[u8], info: &[u8], key_len: usize) -> Vec<u8> {
+    assert!(key_len <= 255 * 32, "HKDF can't produce more than 255 blocks");
+
+    let mut key = Vec::with_capacity(key_len);
+    let mut t: Vec<u8> = Vec::new();
+    for block_idx in 1..=key_len.div_ceil(32) as u8 {
+        t.extend_from_slice(info);
+        t.push(block_idx);
+        t = hmac_sha256(prk, &t).to_vec();
+        key.extend_from_slice(&t);
+    }
+
+    key.truncate(key_len);
+    key
+}
+
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], key_len: usize) -> Vec<u8> {
+    hkdf_expand_sha256(&hkdf_extract_sha256(salt, ikm), info, key_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::from_hex;
+
+    // RFC 7914, section 11
+    #[test]
+    pub fn test_pbkdf2_hmac_sha256() {
+        assert_eq!(
+            pbkdf2_hmac_sha256(b"passwd", b"salt", 1, 64),
+            from_hex("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                      49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783").unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_pbkdf2_hmac_sha256_iterations() {
+        assert_eq!(
+            pbkdf2_hmac_sha256(b"password", b"salt", 2, 32),
+            from_hex("ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43").unwrap()
+        );
+    }
+
+    // RFC 5869, appendix A.1
+    #[test]
+    pub fn test_hkdf_sha256() {
+        let ikm = [0x0bu8; 22];
+        let salt = from_hex("000102030405060708090a0b0c").unwrap();
+        let info = from_hex("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        let prk = hkdf_extract_sha256(&salt, &ikm);
+        assert_eq!(prk.to_vec(), from_hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5").unwrap());
+        assert_eq!(
+            hkdf_expand_sha256(&prk, &info, 42),
+            from_hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+                      34007208d5b887185865").unwrap()
+        );
+    }
+
+    // RFC 5869, appendix A.3
+    #[test]
+    pub fn test_hkdf_sha256_empty_salt() {
+        assert_eq!(
+            hkdf_sha256(&[], &[0x0bu8; 22], &[], 42),
+            from_hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+                      9d201395faa4b61a96c8").unwrap()
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cipher::{self, Cipher};
+use crate::kdf::hkdf_sha256;
+use crate::utils::{self, AlgorithmError};
+
+/// Length of keys derived from shared secrets; enough for AES-256.
+pub const SESSION_KEY_SIZE: usize = 32;
+
+fn random(n: usize) -> Result<Vec<u8>, AlgorithmError> {
+    utils::random_bytes(n).map_err(|e| AlgorithmError { what: format!("Can't generate random bytes: {}", e) })
+}
+
+/// Finite-field Diffie–Hellman over the MODP groups of RFC 3526.
+pub mod ffdh {
+    use super::*;
+    use crate::bigint::BigUint;
+
+    /// Private exponents are this long, which is more than twice the security level of any group here.
+    const EXPONENT_SIZE: usize = 64;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Group {
+        pub name: &'static str,
+        prime: &'static str,
+    }
+
+    /// All groups use generator 2
+    pub const GENERATOR: u64 = 2;
+
+    pub const MODP_2048: Group = Group {
+        name: "modp2048",
+        prime: "\
+        FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+        020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+        4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+        EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05\
+        98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB\
+        9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+        E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718\
+        3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+    };
+
+    pub const MODP_3072: Group = Group {
+        name: "modp3072",
+        prime: "\
+        FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+        020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+        4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+        EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05\
+        98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB\
+        9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+        E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718\
+        3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33\
+        A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7\
+        ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864\
+        D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2\
+        08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF",
+    };
+
+    pub const MODP_4096: Group = Group {
+        name: "modp4096",
+        prime: "\
+        FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74\
+        020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+        4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+        EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05\
+        98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB\
+        9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+        E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718\
+        3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33\
+        A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7\
+        ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864\
+        D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2\
+        08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D7\
+        88719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8\
+        DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2\
+        233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA9\
+        93B4EA988D8FDDC186FFB7DC90A6C08F4DF435C934063199FFFFFFFFFFFFFFFF",
+    };
+
+    pub const GROUPS: [&Group; 3] = [&MODP_2048, &MODP_3072, &MODP_4096];
+
+    impl Group {
+        pub fn prime(&self) -> BigUint {
+            BigUint::from_str_radix(self.prime, 16).unwrap()
+        }
+
+        /// Length of public values and shared secrets in bytes
+        pub fn size(&self) -> usize {
+            self.prime.len() / 2
+        }
+    }
+
+    pub fn group(name: &str) -> Option<&'static Group> {
+        GROUPS.iter().find(|g| g.name == name).copied()
+    }
+
+    /// Returns private exponent and public value g^x mod p, both big-endian.
+    pub fn generate(group: &Group) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+        let private = random(EXPONENT_SIZE)?;
+        let public = public_key(group, &private);
+        Ok((private, public))
+    }
+
+    pub fn public_key(group: &Group, private: &[u8]) -> Vec<u8> {
+        let y = BigUint::from(GENERATOR).modpow(&BigUint::from_bytes_be(private), &group.prime());
+        y.to_bytes_be_padded(group.size()).unwrap()
+    }
+
+    /// Computes the shared secret, rejecting peer values outside of (1, p - 1), which would
+    /// force the secret into a tiny subgroup.
+    pub fn shared_secret(group: &Group, private: &[u8], peer_public: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        let p = group.prime();
+        let y = BigUint::from_bytes_be(peer_public);
+        if y <= BigUint::one() || y >= &p - &BigUint::one() {
+            return Err(AlgorithmError { what: format!("Invalid {} public value", group.name) });
+        }
+        let secret = y.modpow(&BigUint::from_bytes_be(private), &p);
+        Ok(secret.to_bytes_be_padded(group.size()).unwrap())
+    }
+}
+
+/// X25519 function of RFC 7748.
+pub mod x25519 {
+    use super::*;
+
+    pub const KEY_SIZE: usize = 32;
+
+    /// u-coordinate of the base point
+    pub const BASE_POINT: [u8; KEY_SIZE] = {
+        let mut point = [0u8; KEY_SIZE];
+        point[0] = 9;
+        point
+    };
+
+    /// (A - 2) / 4 for curve25519
+    const A24: u64 = 121665;
+
+    const MASK_51: u64 = (1 << 51) - 1;
+
+    /// Element of GF(2^255 - 19) as five 51-bit limbs, little-endian. Limbs may exceed 51 bits
+    /// slightly between reductions.
+    #[derive(Clone, Copy)]
+    struct Fe([u64; 5]);
+
+    impl Fe {
+        const ZERO: Fe = Fe([0; 5]);
+        const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+
+        fn from_bytes(bytes: &[u8; KEY_SIZE]) -> Fe {
+            let mut w = [0u64; 4];
+            for (i, w) in w.iter_mut().enumerate() {
+                *w = u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap());
+            }
+            // top bit is ignored, as RFC 7748 requires
+            Fe([
+                w[0] & MASK_51,
+                (w[0] >> 51 | w[1] << 13) & MASK_51,
+                (w[1] >> 38 | w[2] << 26) & MASK_51,
+                (w[2] >> 25 | w[3] << 39) & MASK_51,
+                (w[3] >> 12) & MASK_51,
+            ])
+        }
+
+        fn to_bytes(self) -> [u8; KEY_SIZE] {
+            let mut h = Fe::carry([self.0[0] as u128, self.0[1] as u128, self.0[2] as u128,
+                                   self.0[3] as u128, self.0[4] as u128]).0;
+
+            // h < 2p now; subtract p if h >= p, which is when h + 19 overflows 2^255
+            let mut q = (h[0] + 19) >> 51;
+            for limb in h[1..].iter() {
+                q = (limb + q) >> 51;
+            }
+            h[0] += 19 * q;
+            for i in 0..4 {
+                h[i + 1] += h[i] >> 51;
+                h[i] &= MASK_51;
+            }
+            h[4] &= MASK_51;
+
+            let mut result = [0u8; KEY_SIZE];
+            let (mut acc, mut acc_bits, mut pos) = (0u128, 0, 0);
+            for &limb in h.iter() {
+                acc |= (limb as u128) << acc_bits;
+                acc_bits += 51;
+                while acc_bits >= 8 && pos < KEY_SIZE {
+                    result[pos] = acc as u8;
+                    acc >>= 8;
+                    acc_bits -= 8;
+                    pos += 1;
+                }
+            }
+            result[KEY_SIZE - 1] |= acc as u8;
+            result
+        }
+
+        /// Propagates carries, so that every limb is below 2^51 plus a small excess in the lowest one
+        fn carry(mut r: [u128; 5]) -> Fe {
+            for i in 0..4 {
+                r[i + 1] += r[i] >> 51;
+                r[i] &= MASK_51 as u128;
+            }
+            r[0] += 19 * (r[4] >> 51);
+            r[4] &= MASK_51 as u128;
+            r[1] += r[0] >> 51;
+            r[0] &= MASK_51 as u128;
+            Fe([r[0] as u64, r[1] as u64, r[2] as u64, r[3] as u64, r[4] as u64])
+        }
+
+        fn add(&self, other: &Fe) -> Fe {
+            let (a, b) = (&self.0, &other.0);
+            Fe::carry([(a[0] + b[0]) as u128, (a[1] + b[1]) as u128, (a[2] + b[2]) as u128,
+                       (a[3] + b[3]) as u128, (a[4] + b[4]) as u128])
+        }
+
+        fn sub(&self, other: &Fe) -> Fe {
+            // adding 2p keeps limbs non-negative
+            let (a, b) = (&self.0, &other.0);
+            Fe::carry([(a[0] + 0xfffffffffffda - b[0]) as u128, (a[1] + 0xffffffffffffe - b[1]) as u128,
+                       (a[2] + 0xffffffffffffe - b[2]) as u128, (a[3] + 0xffffffffffffe - b[3]) as u128,
+                       (a[4] + 0xffffffffffffe - b[4]) as u128])
+        }
+
+        fn mul(&self, other: &Fe) -> Fe {
+            let a = self.0.map(|x| x as u128);
+            let b = other.0.map(|x| x as u128);
+            // 2^255 = 19 (mod p), so products that overflow limb 4 wrap around multiplied by 19
+            let b19 = b.map(|x| 19 * x);
+            Fe::carry([
+                a[0] * b[0] + a[1] * b19[4] + a[2] * b19[3] + a[3] * b19[2] + a[4] * b19[1],
+                a[0] * b[1] + a[1] * b[0] + a[2] * b19[4] + a[3] * b19[3] + a[4] * b19[2],
+                a[0] * b[2] + a[1] * b[1] + a[2] * b[0] + a[3] * b19[4] + a[4] * b19[3],
+                a[0] * b[3] + a[1] * b[2] + a[2] * b[1] + a[3] * b[0] + a[4] * b19[4],
+                a[0] * b[4] + a[1] * b[3] + a[2] * b[2] + a[3] * b[1] + a[4] * b[0],
+            ])
+        }
+
+        fn square(&self) -> Fe {
+            self.mul(self)
+        }
+
+        fn mul_small(&self, n: u64) -> Fe {
+            Fe::carry(self.0.map(|x| x as u128 * n as u128))
+        }
+
+        /// a^(p - 2), which is the inverse by Fermat's little theorem
+        fn invert(&self) -> Fe {
+            // p - 2 = 2^255 - 21, little-endian
+            let mut exponent = [0xffu8; KEY_SIZE];
+            exponent[0] = 0xeb;
+            exponent[KEY_SIZE - 1] = 0x7f;
+
+            let mut result = Fe::ONE;
+            for i in (0..255).rev() {
+                result = result.square();
+                if exponent[i / 8] >> (i % 8) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+            result
+        }
+
+        /// Swaps values if `swap` is 1, without branching on it
+        fn cswap(swap: u64, a: &mut Fe, b: &mut Fe) {
+            let mask = 0u64.wrapping_sub(swap);
+            for (x, y) in a.0.iter_mut().zip(b.0.iter_mut()) {
+                let t = mask & (*x ^ *y);
+                *x ^= t;
+                *y ^= t;
+            }
+        }
+    }
+
+    /// Scalar multiplication on the Montgomery ladder (RFC 7748, section 5).
+    pub fn x25519(scalar: &[u8; KEY_SIZE], u: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+        let mut k = *scalar;
+        k[0] &= 248;
+        k[31] &= 127;
+        k[31] |= 64;
+
+        let x1 = Fe::from_bytes(u);
+        let (mut x2, mut z2, mut x3, mut z3) = (Fe::ONE, Fe::ZERO, x1, Fe::ONE);
+        let mut swap = 0u64;
+
+        for t in (0..255).rev() {
+            let k_t = (k[t / 8] >> (t % 8) & 1) as u64;
+            swap ^= k_t;
+            Fe::cswap(swap, &mut x2, &mut x3);
+            Fe::cswap(swap, &mut z2, &mut z3);
+            swap = k_t;
+
+            let a = x2.add(&z2);
+            let aa = a.square();
+            let b = x2.sub(&z2);
+            let bb = b.square();
+            let e = aa.sub(&bb);
+            let c = x3.add(&z3);
+            let d = x3.sub(&z3);
+            let da = d.mul(&a);
+            let cb = c.mul(&b);
+            x3 = da.add(&cb).square();
+            z3 = x1.mul(&da.sub(&cb).square());
+            x2 = aa.mul(&bb);
+            z2 = e.mul(&aa.add(&e.mul_small(A24)));
+        }
+        Fe::cswap(swap, &mut x2, &mut x3);
+        Fe::cswap(swap, &mut z2, &mut z3);
+
+        x2.mul(&z2.invert()).to_bytes()
+    }
+
+    /// Returns private scalar and public value.
+    pub fn generate() -> Result<([u8; KEY_SIZE], [u8; KEY_SIZE]), AlgorithmError> {
+        let private: [u8; KEY_SIZE] = random(KEY_SIZE)?.try_into().unwrap();
+        Ok((private, public_key(&private)))
+    }
+
+    pub fn public_key(private: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+        x25519(private, &BASE_POINT)
+    }
+
+    /// Computes the shared secret, rejecting the all-zero result of low-order peer points.
+    pub fn shared_secret(private: &[u8; KEY_SIZE], peer_public: &[u8; KEY_SIZE]) -> Result<[u8; KEY_SIZE], AlgorithmError> {
+        let secret = x25519(private, peer_public);
+        if utils::constant_time_eq(&secret, &[0u8; KEY_SIZE]) {
+            return Err(AlgorithmError { what: "Invalid X25519 public value".to_string() });
+        }
+        Ok(secret)
+    }
+}
+
+/// Key agreement scheme, selectable by name: "x25519" or one of the `ffdh::GROUPS` names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scheme {
+    Ffdh(&'static ffdh::Group),
+    X25519,
+}
+
+impl Scheme {
+
+    /// Returns private key and public value as bytes.
+    pub fn generate(&self) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+        match self {
+            Scheme::Ffdh(group) => ffdh::generate(group),
+            Scheme::X25519 => x25519::generate().map(|(private, public)| (private.to_vec(), public.to_vec())),
+        }
+    }
+
+    pub fn shared_secret(&self, private: &[u8], peer_public: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        match self {
+            Scheme::Ffdh(group) => ffdh::shared_secret(group, private, peer_public),
+            Scheme::X25519 => match (private.try_into(), peer_public.try_into()) {
+                (Ok(private), Ok(peer_public)) => Ok(x25519::shared_secret(private, peer_public)?.to_vec()),
+                _ => Err(AlgorithmError { what: format!("X25519 keys must be {} bytes long", x25519::KEY_SIZE) }),
+            },
+        }
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "x25519" => Ok(Scheme::X25519),
+            name => ffdh::group(name).map(Scheme::Ffdh).ok_or_else(|| {
+                let names = ffdh::GROUPS.iter().map(|g| g.name).collect::<Vec<_>>();
+                AlgorithmError { what: format!("Unknown key agreement scheme: {} (known: x25519, {})", s, names.join(", ")) }
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scheme::Ffdh(group) => write!(f, "{}", group.name),
+            Scheme::X25519 => write!(f, "x25519"),
+        }
+    }
+}
+
+/// Derives a session key from the shared secret with HKDF-SHA256. The cipher name goes into
+/// HKDF info, so that different ciphers never share a key.
+pub fn session_key(shared_secret: &[u8], cipher_name: &str) -> Vec<u8> {
+    let info = format!("security session key: {}", cipher_name.to_lowercase());
+    hkdf_sha256(&[], shared_secret, info.as_bytes(), SESSION_KEY_SIZE)
+}
+
+/// Cipher from `cipher::by_name` keyed with the session key.
+pub fn session_cipher(shared_secret: &[u8], cipher_name: &str) -> Result<Box<dyn Cipher>, AlgorithmError> {
+    cipher::by_name(cipher_name, &session_key(shared_secret, cipher_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::from_hex;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        from_hex(s).unwrap().try_into().unwrap()
+    }
+
+    // RFC 7748, section 5.2
+    #[test]
+    pub fn test_x25519_function() {
+        let scalar = hex32("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        let u = hex32("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+        assert_eq!(
+            x25519::x25519(&scalar, &u),
+            hex32("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+
+        let scalar = hex32("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d");
+        let u = hex32("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493");
+        assert_eq!(
+            x25519::x25519(&scalar, &u),
+            hex32("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957")
+        );
+    }
+
+    // RFC 7748, section 6.1
+    #[test]
+    pub fn test_x25519_agreement() {
+        let alice = hex32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = hex32("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_public = x25519::public_key(&alice);
+        let bob_public = x25519::public_key(&bob);
+        assert_eq!(alice_public, hex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+        assert_eq!(bob_public, hex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+
+        let shared = hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(x25519::shared_secret(&alice, &bob_public).unwrap(), shared);
+        assert_eq!(x25519::shared_secret(&bob, &alice_public).unwrap(), shared);
+
+        assert!(x25519::shared_secret(&alice, &[0u8; 32]).is_err());
+    }
+
+    #[test]
+    pub fn test_ffdh_agreement() {
+        let group = &ffdh::MODP_2048;
+        assert_eq!(group.prime().bits(), 2048);
+
+        let (alice, alice_public) = ffdh::generate(group).unwrap();
+        let (bob, bob_public) = ffdh::generate(group).unwrap();
+        let shared = ffdh::shared_secret(group, &alice, &bob_public).unwrap();
+        assert_eq!(shared, ffdh::shared_secret(group, &bob, &alice_public).unwrap());
+        assert_eq!(shared.len(), 256);
+
+        assert!(ffdh::shared_secret(group, &alice, &[1]).is_err());
+        let p_minus_one = (&group.prime() - &crate::bigint::BigUint::one()).to_bytes_be();
+        assert!(ffdh::shared_secret(group, &alice, &p_minus_one).is_err());
+    }
+
+    #[test]
+    pub fn test_session_cipher() {
+        let scheme: Scheme = "x25519".parse().unwrap();
+        let (alice, alice_public) = scheme.generate().unwrap();
+        let (bob, bob_public) = scheme.generate().unwrap();
+
+        let mut message = b"meet me at the usual place".to_vec();
+        session_cipher(&scheme.shared_secret(&alice, &bob_public).unwrap(), "aes-ctr").unwrap()
+            .encrypt(&mut message).unwrap();
+        session_cipher(&scheme.shared_secret(&bob, &alice_public).unwrap(), "aes-ctr").unwrap()
+            .decrypt(&mut message).unwrap();
+        assert_eq!(message, b"meet me at the usual place".to_vec());
+
+        assert_ne!(session_key(&[1, 2, 3], "aes-ctr"), session_key(&[1, 2, 3], "aes-cbc"));
+        assert!("modp1024".parse::<Scheme>().is_err());
+    }
+}
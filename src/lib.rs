@@ -6,12 +6,40 @@ mod text_hiding;
 mod xor;
 
 pub mod utils;
-pub use utils::OperationMode;
+pub use utils::{OperationMode, AlgorithmError};
 
 pub mod checksum;
 
 pub mod sig_search;
 
+pub mod aes;
+
+pub mod kdf;
+
+pub mod container;
+
+pub mod cipher;
+
+pub mod classical;
+
+pub mod cryptanalysis;
+
+pub mod otp;
+
+pub mod bigint;
+
+pub mod codec;
+
+pub mod rsa;
+
+pub mod key_exchange;
+
+pub mod shamir;
+
+pub mod hotp;
+
+pub mod padding_oracle;
+
 pub use text_stats::*;
 pub use text_hiding::*;
 pub use xor::*;
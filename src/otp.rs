@@ -0,0 +1,246 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::utils::{self, AlgorithmError};
+use crate::xor::xor_inplace;
+
+pub const MAGIC: &[u8; 4] = b"OTP1";
+
+/// magic, pad offset (u64), message length (u64)
+pub const HEADER_SIZE: usize = 4 + 8 + 8;
+
+const GENERATE_CHUNK_SIZE: usize = 1 << 16;
+
+/// Writes a pad of given size, filled with bytes from the OS CSPRNG.
+pub fn generate_pad<W: Write>(mut output: W, size: u64) -> io::Result<()> {
+    let mut left = size;
+    while left > 0 {
+        let n = left.min(GENERATE_CHUNK_SIZE as u64) as usize;
+        output.write_all(&utils::random_bytes(n)?)?;
+        left -= n as u64;
+    }
+    output.flush()
+}
+
+/// Ledger of consumed pad ranges. Stored as a text file with one `start end` (end exclusive)
+/// line per range, next to the pad: see `ledger_path`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ledger {
+    /// Sorted, non-overlapping and non-adjacent half-open ranges
+    ranges: Vec<(u64, u64)>,
+}
+
+impl Ledger {
+
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+
+    /// Total number of consumed bytes
+    pub fn consumed(&self) -> u64 {
+        self.ranges.iter().map(|(start, end)| end - start).sum()
+    }
+
+    pub fn is_free(&self, start: u64, len: u64) -> bool {
+        let end = start + len;
+        self.ranges.iter().all(|&(s, e)| e <= start || end <= s)
+    }
+
+    /// Finds the first free range of given length in a pad of `pad_size` bytes.
+    pub fn find_free(&self, len: u64, pad_size: u64) -> Option<u64> {
+        let mut candidate = 0u64;
+        for &(start, end) in self.ranges.iter() {
+            if start >= candidate + len {
+                break;
+            }
+            candidate = candidate.max(end);
+        }
+        if candidate + len <= pad_size { Some(candidate) } else { None }
+    }
+
+    /// Marks range as consumed, refusing to do so if any part of it was consumed before.
+    pub fn reserve(&mut self, start: u64, len: u64) -> Result<(), AlgorithmError> {
+        if !self.is_free(start, len) {
+            return Err(AlgorithmError {
+                what: format!("Pad range {}..{} was already used, refusing to reuse it", start, start + len)
+            });
+        }
+        self.record(start, len);
+        Ok(())
+    }
+
+    /// Marks range as consumed, whether or not it was consumed before.
+    pub fn record(&mut self, start: u64, len: u64) {
+        if len == 0 {
+            return;
+        }
+        self.ranges.push((start, start + len));
+        self.ranges.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in self.ranges.iter() {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    /// Loads the ledger; missing file means that nothing was consumed yet.
+    pub fn load(path: &Path) -> Result<Ledger, AlgorithmError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => contents.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Ledger::default()),
+            Err(e) => Err(AlgorithmError { what: format!("Can't read ledger {:?}: {}", path, e) }),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (start, end) in self.ranges.iter() {
+            writeln!(f, "{} {}", start, end)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Ledger {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ledger = Ledger::default();
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let range = line
+                .split_once(' ')
+                .and_then(|(start, end)| Some((start.parse::<u64>().ok()?, end.trim().parse::<u64>().ok()?)))
+                .filter(|(start, end)| start <= end);
+            match range {
+                Some((start, end)) => ledger.record(start, end - start),
+                None => return Err(AlgorithmError { what: format!("Malformed ledger line: {}", line) }),
+            }
+        }
+        Ok(ledger)
+    }
+}
+
+/// Ledger file which belongs to the pad
+pub fn ledger_path(pad_path: &Path) -> PathBuf {
+    let mut name = pad_path.as_os_str().to_owned();
+    name.push(".ledger");
+    PathBuf::from(name)
+}
+
+fn read_pad<P: Read + Seek>(pad: &mut P, offset: u64, len: usize) -> Result<Vec<u8>, AlgorithmError> {
+    let mut buf = vec![0u8; len];
+    pad.seek(SeekFrom::Start(offset))
+        .and_then(|_| pad.read_exact(&mut buf))
+        .map_err(|e| AlgorithmError { what: format!("Can't read {} pad bytes at offset {}: {}", len, offset, e) })?;
+    Ok(buf)
+}
+
+fn pad_size<P: Seek>(pad: &mut P) -> Result<u64, AlgorithmError> {
+    pad.seek(SeekFrom::End(0)).map_err(|e| AlgorithmError { what: format!("Can't seek pad: {}", e) })
+}
+
+/// Encrypts the message with the first unused part of the pad and records it as consumed.
+/// Output is a header with the pad offset followed by the ciphertext.
+pub fn encrypt<P: Read + Seek>(pad: &mut P, ledger: &mut Ledger, plaintext: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+    let len = plaintext.len() as u64;
+    let size = pad_size(pad)?;
+    let offset = ledger.find_free(len, size).ok_or_else(|| AlgorithmError {
+        what: format!("Pad has no unused range of {} bytes ({} of {} bytes consumed)", len, ledger.consumed(), size)
+    })?;
+
+    let key = read_pad(pad, offset, plaintext.len())?;
+    ledger.reserve(offset, len)?;
+
+    let mut result = Vec::with_capacity(HEADER_SIZE + plaintext.len());
+    result.extend_from_slice(MAGIC);
+    result.extend_from_slice(&offset.to_be_bytes());
+    result.extend_from_slice(&len.to_be_bytes());
+
+    let mut ciphertext = plaintext.to_vec();
+    xor_inplace(&mut ciphertext, &key);
+    result.extend(ciphertext);
+
+    Ok(result)
+}
+
+/// Decrypts the message using pad offset from its header, and records the range as consumed,
+/// so that it is never used for encryption by this side either.
+pub fn decrypt<P: Read + Seek>(pad: &mut P, ledger: &mut Ledger, message: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+    if message.len() < HEADER_SIZE || &message[..4] != MAGIC {
+        return Err(AlgorithmError { what: "Not a one-time pad message".to_string() });
+    }
+
+    let offset = u64::from_be_bytes(message[4..12].try_into().unwrap());
+    let len = u64::from_be_bytes(message[12..20].try_into().unwrap());
+    if len != (message.len() - HEADER_SIZE) as u64 {
+        return Err(AlgorithmError {
+            what: format!("Message is truncated: expected {} bytes, got {}", len, message.len() - HEADER_SIZE)
+        });
+    }
+
+    let key = read_pad(pad, offset, len as usize)?;
+    ledger.record(offset, len);
+
+    let mut plaintext = message[HEADER_SIZE..].to_vec();
+    xor_inplace(&mut plaintext, &key);
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    pub fn test_ledger() {
+        let mut ledger = Ledger::default();
+        assert!(ledger.reserve(10, 5).is_ok());
+        assert!(ledger.reserve(0, 10).is_ok());
+        assert_eq!(ledger.ranges(), &[(0, 15)]);
+
+        assert!(ledger.reserve(14, 2).is_err());
+        assert!(ledger.reserve(20, 5).is_ok());
+        assert_eq!(ledger.find_free(5, 100), Some(15));
+        assert_eq!(ledger.find_free(6, 100), Some(25));
+        assert_eq!(ledger.find_free(80, 100), None);
+
+        assert_eq!(ledger.to_string().parse::<Ledger>().unwrap(), ledger);
+        assert!("1 x".parse::<Ledger>().is_err());
+    }
+
+    #[test]
+    pub fn test_encrypt_decrypt() {
+        let mut pad_bytes = Vec::new();
+        generate_pad(&mut pad_bytes, 64).unwrap();
+        assert_eq!(pad_bytes.len(), 64);
+        let mut pad = Cursor::new(pad_bytes);
+
+        let mut sender = Ledger::default();
+        let first = encrypt(&mut pad, &mut sender, b"first message").unwrap();
+        let second = encrypt(&mut pad, &mut sender, b"second message").unwrap();
+        assert_eq!(sender.ranges(), &[(0, 27)]);
+
+        let mut receiver = Ledger::default();
+        assert_eq!(decrypt(&mut pad, &mut receiver, &second).unwrap(), b"second message".to_vec());
+        assert_eq!(decrypt(&mut pad, &mut receiver, &first).unwrap(), b"first message".to_vec());
+        assert_eq!(receiver, sender);
+
+        // receiver's reply must not reuse the ranges it has seen
+        let reply = encrypt(&mut pad, &mut receiver, b"reply").unwrap();
+        assert_eq!(u64::from_be_bytes(reply[4..12].try_into().unwrap()), 27);
+
+        assert!(encrypt(&mut pad, &mut sender, &[0u8; 40]).is_err());
+    }
+}
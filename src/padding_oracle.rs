@@ -0,0 +1,287 @@
+//! Padding-oracle lab: a target that decrypts AES-CBC and reveals nothing but whether PKCS#7
+//! padding was valid, and an attacker that recovers the plaintext through it (Vaudenay, 2002).
+//!
+//! The target is available either in-process (`LocalOracle`) or as a TCP service (`serve`,
+//! queried with `TcpOracle`). The service protocol is line-based: the client sends hex of
+//! IV followed by ciphertext, the server answers `valid`, `invalid`, or `malformed` for a request
+//! it can't parse.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::aes::{self, Aes, Block, BLOCK_SIZE};
+use crate::utils::{self, AlgorithmError};
+
+pub trait Oracle {
+    /// Whether the ciphertext decrypts to correctly padded plaintext
+    fn padding_is_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> Result<bool, AlgorithmError>;
+}
+
+/// In-process target with a counter of answered queries.
+pub struct LocalOracle {
+    cipher: Aes,
+    queries: u64,
+}
+
+impl LocalOracle {
+    pub fn new(key: &[u8]) -> Result<LocalOracle, AlgorithmError> {
+        Ok(LocalOracle { cipher: Aes::new(key)?, queries: 0 })
+    }
+
+    /// Target with a random AES-128 key, unknown to anybody
+    pub fn with_random_key() -> Result<LocalOracle, AlgorithmError> {
+        let key = utils::random_bytes(16).map_err(|e| AlgorithmError { what: format!("Can't generate key: {}", e) })?;
+        LocalOracle::new(&key)
+    }
+
+    /// Encrypts a challenge plaintext with a random IV
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<(Block, Vec<u8>), AlgorithmError> {
+        let mut iv = [0u8; BLOCK_SIZE];
+        let random = utils::random_bytes(BLOCK_SIZE).map_err(|e| AlgorithmError { what: format!("Can't generate IV: {}", e) })?;
+        iv.copy_from_slice(&random);
+        Ok((iv, aes::cbc::encrypt(&self.cipher, &iv, plaintext)))
+    }
+
+    pub fn queries(&self) -> u64 {
+        self.queries
+    }
+}
+
+impl Oracle for LocalOracle {
+    fn padding_is_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> Result<bool, AlgorithmError> {
+        self.queries += 1;
+        Ok(aes::cbc::decrypt(&self.cipher, iv, ciphertext).is_ok())
+    }
+}
+
+/// Serves the oracle over TCP, one client at a time, until the listener fails. A client that
+/// disconnects or breaks the connection is dropped without stopping the service.
+pub fn serve(listener: TcpListener, oracle: &mut LocalOracle) -> io::Result<()> {
+    for stream in listener.incoming() {
+        // errors of a single client only end its session
+        let _ = serve_client(stream?, oracle);
+    }
+    Ok(())
+}
+
+/// Answers queries of one client until it disconnects
+fn serve_client(stream: TcpStream, oracle: &mut LocalOracle) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let request = std::str::from_utf8(&line).map_err(|e| e.to_string()).and_then(|l| utils::from_hex(l.trim()));
+        let answer = match request {
+            Ok(data) if data.len() >= BLOCK_SIZE => {
+                let (iv, ciphertext) = data.split_at(BLOCK_SIZE);
+                match oracle.padding_is_valid(&to_block(iv), ciphertext) {
+                    Ok(true) => "valid",
+                    _ => "invalid",
+                }
+            },
+            _ => "malformed",
+        };
+        writer.write_all(format!("{}\n", answer).as_bytes())?;
+    }
+}
+
+/// Client of a target started with `serve`
+pub struct TcpOracle {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl TcpOracle {
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<TcpOracle> {
+        let writer = TcpStream::connect(address)?;
+        writer.set_nodelay(true)?;
+        Ok(TcpOracle { reader: BufReader::new(writer.try_clone()?), writer })
+    }
+}
+
+impl Oracle for TcpOracle {
+    fn padding_is_valid(&mut self, iv: &Block, ciphertext: &[u8]) -> Result<bool, AlgorithmError> {
+        let io_error = |e: io::Error| AlgorithmError { what: format!("Oracle connection failed: {}", e) };
+
+        let request = format!("{}{}\n", utils::to_hex(iv), utils::to_hex(ciphertext));
+        self.writer.write_all(request.as_bytes()).map_err(io_error)?;
+        let mut answer = String::new();
+        self.reader.read_line(&mut answer).map_err(io_error)?;
+        match answer.trim() {
+            "valid" => Ok(true),
+            "invalid" => Ok(false),
+            other => Err(AlgorithmError { what: format!("Unexpected oracle answer: {:?}", other) }),
+        }
+    }
+}
+
+fn to_block(data: &[u8]) -> Block {
+    let mut block = [0u8; BLOCK_SIZE];
+    block.copy_from_slice(data);
+    block
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub block: usize,
+    pub blocks: usize,
+    /// Bytes of the current block recovered so far, from its end
+    pub recovered: usize,
+    pub queries: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovered {
+    pub plaintext: Vec<u8>,
+    pub queries: u64,
+}
+
+/// Counts queries passed to the oracle
+struct Counting<'a> {
+    oracle: &'a mut dyn Oracle,
+    queries: u64,
+}
+
+impl Counting<'_> {
+    fn query(&mut self, iv: &Block, block: &Block) -> Result<bool, AlgorithmError> {
+        self.queries += 1;
+        self.oracle.padding_is_valid(iv, block)
+    }
+}
+
+/// Finds D(block), the block cipher decryption of a single ciphertext block, by forging the
+/// previous block so that the padding comes out as 01, 02 02, and so on.
+fn intermediate<F>(oracle: &mut Counting, block: &Block, mut progress: F) -> Result<Block, AlgorithmError>
+    where F: FnMut(usize, u64)
+{
+    let mut intermediate = [0u8; BLOCK_SIZE];
+
+    for pad in 1..=BLOCK_SIZE {
+        let i = BLOCK_SIZE - pad;
+        let mut forged = [0u8; BLOCK_SIZE];
+        for j in i + 1..BLOCK_SIZE {
+            forged[j] = intermediate[j] ^ pad as u8;
+        }
+
+        let mut found = None;
+        for guess in 0..=255u8 {
+            forged[i] = guess;
+            if !oracle.query(&forged, block)? {
+                continue;
+            }
+            // for the last byte, valid padding could also be 02 02 and so on: changing the
+            // byte before it breaks those, but not 01
+            if pad == 1 {
+                let mut check = forged;
+                check[i - 1] ^= 0xff;
+                if !oracle.query(&check, block)? {
+                    continue;
+                }
+            }
+            found = Some(guess);
+            break;
+        }
+
+        match found {
+            Some(guess) => intermediate[i] = guess ^ pad as u8,
+            None => return Err(AlgorithmError { what: "Oracle never reported valid padding".to_string() }),
+        }
+        progress(pad, oracle.queries);
+    }
+
+    Ok(intermediate)
+}
+
+/// Recovers the plaintext of an AES-CBC ciphertext using only the padding oracle.
+pub fn attack<F>(oracle: &mut dyn Oracle, iv: &Block, ciphertext: &[u8], mut progress: F) -> Result<Recovered, AlgorithmError>
+    where F: FnMut(&Progress)
+{
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(AlgorithmError { what: "Ciphertext length is not a positive multiple of block size".to_string() });
+    }
+
+    let mut oracle = Counting { oracle, queries: 0 };
+    let blocks = ciphertext.len() / BLOCK_SIZE;
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+
+    let mut previous = *iv;
+    for (block_idx, chunk) in ciphertext.chunks(BLOCK_SIZE).enumerate() {
+        let block = to_block(chunk);
+        let decrypted = intermediate(&mut oracle, &block, |recovered, queries| {
+            progress(&Progress { block: block_idx, blocks, recovered, queries })
+        })?;
+        plaintext.extend(decrypted.iter().zip(previous.iter()).map(|(d, p)| d ^ p));
+        previous = block;
+    }
+
+    aes::pkcs7_unpad(&mut plaintext, BLOCK_SIZE)?;
+    Ok(Recovered { plaintext, queries: oracle.queries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"Attack at dawn! The password is 'swordfish', don't tell anyone.";
+
+    #[test]
+    pub fn test_attack_local_oracle() {
+        let mut oracle = LocalOracle::with_random_key().unwrap();
+        let (iv, ciphertext) = oracle.encrypt(SECRET).unwrap();
+
+        let mut updates = 0;
+        let recovered = attack(&mut oracle, &iv, &ciphertext, |_| updates += 1).unwrap();
+        assert_eq!(recovered.plaintext, SECRET.to_vec());
+        assert_eq!(recovered.queries, oracle.queries());
+        assert_eq!(updates, ciphertext.len());
+        // 128 queries per byte on average, plus confirmation queries
+        assert!(recovered.queries < 256 * ciphertext.len() as u64);
+    }
+
+    #[test]
+    pub fn test_attack_tcp_oracle() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let mut target = LocalOracle::with_random_key().unwrap();
+        let (iv, ciphertext) = target.encrypt(b"sent over the wire").unwrap();
+        std::thread::spawn(move || serve(listener, &mut target));
+
+        let mut oracle = TcpOracle::connect(address).unwrap();
+        let recovered = attack(&mut oracle, &iv, &ciphertext, |_| {}).unwrap();
+        assert_eq!(recovered.plaintext, b"sent over the wire".to_vec());
+    }
+
+    #[test]
+    pub fn test_service_survives_bad_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let mut target = LocalOracle::with_random_key().unwrap();
+        let (iv, ciphertext) = target.encrypt(b"still serving").unwrap();
+        std::thread::spawn(move || serve(listener, &mut target));
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(b"\xff\n").unwrap();
+        let mut answer = String::new();
+        BufReader::new(client.try_clone().unwrap()).read_line(&mut answer).unwrap();
+        assert_eq!(answer, "malformed\n");
+        // disconnect in the middle of a request
+        client.write_all(b"00112233").unwrap();
+        drop(client);
+
+        let mut oracle = TcpOracle::connect(address).unwrap();
+        assert!(oracle.padding_is_valid(&iv, &ciphertext).unwrap());
+    }
+
+    #[test]
+    pub fn test_rejects_bad_length() {
+        let mut oracle = LocalOracle::with_random_key().unwrap();
+        assert!(attack(&mut oracle, &[0u8; BLOCK_SIZE], &[0u8; 20], |_| {}).is_err());
+        assert!(attack(&mut oracle, &[0u8; BLOCK_SIZE], &[], |_| {}).is_err());
+    }
+}
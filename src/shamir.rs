@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::checksum::sha::sha256_digest;
+use crate::utils::{self, AlgorithmError};
+
+/// Length of the truncated SHA-256 tag appended to every encoded share
+pub const TAG_SIZE: usize = 4;
+
+/// Arithmetic in GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1. Written without
+/// lookup tables or data-dependent branches, so that timings don't depend on the secret.
+mod gf256 {
+    pub fn mul(mut a: u8, mut b: u8) -> u8 {
+        let mut product = 0u8;
+        for _ in 0..8 {
+            product ^= a & 0u8.wrapping_sub(b & 1);
+            let carry = a >> 7;
+            a <<= 1;
+            a ^= 0x1b & 0u8.wrapping_sub(carry);
+            b >>= 1;
+        }
+        product
+    }
+
+    /// a^254 = a^-1 for non-zero a
+    pub fn inv(a: u8) -> u8 {
+        let mut result = 1u8;
+        for _ in 0..254 {
+            result = mul(result, a);
+        }
+        result
+    }
+
+    /// Evaluates polynomial with given coefficients (lowest degree first) at x
+    pub fn eval(coefficients: &[u8], x: u8) -> u8 {
+        coefficients.iter().rev().fold(0u8, |acc, &c| mul(acc, x) ^ c)
+    }
+}
+
+/// One share of a secret: value of a random polynomial at `index` for every byte of the secret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Share {
+    pub index: u8,
+    pub threshold: u8,
+    pub data: Vec<u8>,
+}
+
+impl Share {
+    fn tag(&self) -> [u8; TAG_SIZE] {
+        let mut tagged = vec![self.index, self.threshold];
+        tagged.extend_from_slice(&self.data);
+        let mut tag = [0u8; TAG_SIZE];
+        tag.copy_from_slice(&sha256_digest(&tagged)[..TAG_SIZE]);
+        tag
+    }
+
+    /// Index, threshold, data and tag
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = vec![self.index, self.threshold];
+        result.extend_from_slice(&self.data);
+        result.extend_from_slice(&self.tag());
+        result
+    }
+
+    /// Parses share, checking its tag.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Share, AlgorithmError> {
+        if bytes.len() < 2 + TAG_SIZE {
+            return Err(AlgorithmError { what: "Share is too short".to_string() });
+        }
+        let (body, tag) = bytes.split_at(bytes.len() - TAG_SIZE);
+        let share = Share { index: body[0], threshold: body[1], data: body[2..].to_vec() };
+        if share.index == 0 || !utils::constant_time_eq(&share.tag(), tag) {
+            return Err(AlgorithmError { what: "Share is corrupted: checksum mismatch".to_string() });
+        }
+        Ok(share)
+    }
+}
+
+/// Hex of `to_bytes`
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", utils::to_hex(&self.to_bytes()))
+    }
+}
+
+impl FromStr for Share {
+    type Err = AlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = utils::from_hex(s.trim()).map_err(|e| AlgorithmError { what: format!("Malformed share: {}", e) })?;
+        Share::from_bytes(&bytes)
+    }
+}
+
+/// Splits secret into `shares` shares, any `threshold` of which recover it.
+pub fn split(secret: &[u8], threshold: u8, shares: u8) -> Result<Vec<Share>, AlgorithmError> {
+    if threshold < 2 || threshold > shares {
+        return Err(AlgorithmError {
+            what: format!("Threshold must be between 2 and the number of shares, got {} of {}", threshold, shares)
+        });
+    }
+
+    let randomness = utils::random_bytes(secret.len() * (threshold as usize - 1))
+        .map_err(|e| AlgorithmError { what: format!("Can't generate random bytes: {}", e) })?;
+
+    let mut result = (1..=shares)
+        .map(|index| Share { index, threshold, data: Vec::with_capacity(secret.len()) })
+        .collect::<Vec<_>>();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for (&byte, random) in secret.iter().zip(randomness.chunks(threshold as usize - 1)) {
+        coefficients[0] = byte;
+        coefficients[1..].copy_from_slice(random);
+        for share in result.iter_mut() {
+            share.data.push(gf256::eval(&coefficients, share.index));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Recovers the secret from at least `threshold` shares with Lagrange interpolation at zero.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, AlgorithmError> {
+    let first = match shares.first() {
+        Some(first) => first,
+        None => return Err(AlgorithmError { what: "No shares given".to_string() }),
+    };
+    if shares.iter().any(|s| s.threshold != first.threshold || s.data.len() != first.data.len()) {
+        return Err(AlgorithmError { what: "Shares belong to different secrets".to_string() });
+    }
+
+    let mut indices = HashSet::new();
+    let shares = shares.iter().filter(|s| indices.insert(s.index)).collect::<Vec<_>>();
+    if shares.len() < first.threshold as usize {
+        return Err(AlgorithmError {
+            what: format!("Need {} distinct shares, got {}", first.threshold, shares.len())
+        });
+    }
+    let shares = &shares[..first.threshold as usize];
+
+    // basis polynomial values at zero: prod x_j / (x_j - x_i), subtraction being xor
+    let weights = shares.iter().map(|si| {
+        shares.iter()
+            .filter(|sj| sj.index != si.index)
+            .fold(1u8, |acc, sj| gf256::mul(acc, gf256::mul(sj.index, gf256::inv(sj.index ^ si.index))))
+    }).collect::<Vec<u8>>();
+
+    Ok((0..first.data.len())
+        .map(|i| shares.iter().zip(weights.iter()).fold(0u8, |acc, (s, &w)| acc ^ gf256::mul(s.data[i], w)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_gf256() {
+        // FIPS-197, section 4.2
+        assert_eq!(gf256::mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf256::mul(0x57, 0x13), 0xfe);
+        for a in 1..=255u8 {
+            assert_eq!(gf256::mul(a, gf256::inv(a)), 1);
+        }
+    }
+
+    #[test]
+    pub fn test_split_combine() {
+        let secret = b"correct horse battery staple".to_vec();
+        let shares = split(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]].iter() {
+            let chosen = subset.iter().map(|&i| shares[i].clone()).collect::<Vec<_>>();
+            assert_eq!(combine(&chosen).unwrap(), secret);
+        }
+
+        assert!(combine(&shares[..2]).is_err());
+        assert!(combine(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+        assert!(split(&secret, 1, 5).is_err());
+        assert!(split(&secret, 6, 5).is_err());
+    }
+
+    #[test]
+    pub fn test_share_encoding() {
+        let shares = split(b"secret", 2, 3).unwrap();
+        let encoded = shares[1].to_string();
+        assert_eq!(encoded.parse::<Share>().unwrap(), shares[1]);
+
+        let mut corrupted = shares[1].to_bytes();
+        corrupted[3] ^= 0x10;
+        assert!(Share::from_bytes(&corrupted).is_err());
+        assert!("0102".parse::<Share>().is_err());
+    }
+}
@@ -23,7 +23,7 @@ pub fn search_file(path: &Path, data: &[u8]) -> io::Result<Option<SearchResult>>
         return Ok(None);
     }
 
-    let automaton = aho_corasick::AhoCorasick::new_auto_configured(&vec![data]);
+    let automaton = aho_corasick::AhoCorasick::new_auto_configured(&[data]);
 
     let res = if metadata.len() < MMAP_THRESHOLD {
         // file is reasonably small, load into memory to speed up search
@@ -1,19 +1,50 @@
+use std::convert::TryInto;
 use std::iter::Iterator;
 
+use crate::checksum::sha::sha256_digest;
+use crate::container::KdfParams;
+use crate::utils::{self, AlgorithmError, OperationMode};
+
 const BITS_IN_BYTE: u8 = 8;
 
 /// Returns an iterator over bits (as bool values) in string.
 pub fn iterate_bits(s: &str) -> impl Iterator<Item=bool> + '_ {
-    s
-        .bytes()
+    iterate_byte_bits(s.as_bytes())
+}
+
+/// Returns an iterator over bits (as bool values) in bytes, least significant bit first.
+pub fn iterate_byte_bits(data: &[u8]) -> impl Iterator<Item=bool> + '_ {
+    data
+        .iter()
         .flat_map(
             move |b|
                 (0..BITS_IN_BYTE).map(
-                    move |i| (b & (1 << i) != 0)
+                    move |i| *b & (1 << i) != 0
                 )
         )
 }
 
+/// Value of a group of bits, least significant bit first
+fn bits_to_value(bits: &[bool]) -> usize {
+    bits.iter().enumerate().fold(0, |value, (i, &bit)| value | (bit as usize) << i)
+}
+
+/// The `count` lowest bits of a value, least significant bit first
+fn value_to_bits(value: usize, count: usize) -> impl Iterator<Item=bool> {
+    (0..count).map(move |i| value & (1 << i) != 0)
+}
+
+/// Fails if `bits` don't fit in the container
+fn check_capacity<C: Carrier + ?Sized>(carrier: &C, container: &str, bits: usize) -> Result<(), AlgorithmError> {
+    let capacity = carrier.capacity(container);
+    if bits > capacity {
+        return Err(AlgorithmError {
+            what: format!("Cannot hide {} bits in a container of {} bits ({})", bits, capacity, carrier.metadata().name)
+        });
+    }
+    Ok(())
+}
+
 /// Accumulates bits from bool iterator into u8 buffer
 pub fn accumulate_bits<Iter>(it: Iter, buf: &mut Vec<u8>)
     where Iter: Iterator<Item=bool>
@@ -46,54 +77,413 @@ pub fn len_bits(s: &str) -> usize {
 
 /// Remove trailing zeros from vector of bytes.
 pub fn remove_trailing_zeros(buf: &mut Vec<u8>) {
-    while buf.last().map_or(false, |&b| b == 0u8) {
+    while buf.last() == Some(&0u8) {
         buf.pop();
     }
 }
 
+pub mod frame {
+    //! Hidden payloads are framed as magic, payload length (u32, big endian), payload and
+    //! a truncated SHA-256 tag of the payload, so that extraction returns exactly the
+    //! embedded bytes and tells an empty or damaged container apart.
+    use super::*;
 
-#[derive(Debug)]
-pub struct AlgorithmError { pub what: String }
+    pub const MAGIC: &[u8; 2] = b"SG";
+    pub const TAG_SIZE: usize = 4;
+    pub const HEADER_SIZE: usize = MAGIC.len() + 4;
+    /// Bytes added to every payload
+    pub const OVERHEAD: usize = HEADER_SIZE + TAG_SIZE;
 
+    fn tag(payload: &[u8]) -> [u8; TAG_SIZE] {
+        let mut tag = [0u8; TAG_SIZE];
+        tag.copy_from_slice(&sha256_digest(payload)[..TAG_SIZE]);
+        tag
+    }
 
-pub mod lines {
-    use super::*;
+    /// Number of bits a payload of `len` bytes takes once framed
+    pub fn framed_len_bits(len: usize) -> usize {
+        (len + OVERHEAD) * BITS_IN_BYTE as usize
+    }
 
-    /// Hide text in the multi-line container
-    pub fn hide(text: &str, container: &mut Vec<String>, secret_char: char) -> Result<String, AlgorithmError> {
-        if len_bits(text) > container.len() {
+    pub fn wrap(payload: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        let len: u32 = payload.len().try_into().map_err(|_| AlgorithmError {
+            what: format!("Payload of {} bytes is too long", payload.len())
+        })?;
+
+        let mut result = Vec::with_capacity(payload.len() + OVERHEAD);
+        result.extend_from_slice(MAGIC);
+        result.extend_from_slice(&len.to_be_bytes());
+        result.extend_from_slice(payload);
+        result.extend_from_slice(&tag(payload));
+        Ok(result)
+    }
+
+    /// Parses a frame from the start of `data`, ignoring whatever follows it.
+    pub fn unwrap(data: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+        if data.len() < OVERHEAD || &data[..MAGIC.len()] != MAGIC {
+            return Err(AlgorithmError { what: "Container holds no hidden payload".to_string() });
+        }
+
+        let len = u32::from_be_bytes(data[MAGIC.len()..HEADER_SIZE].try_into().unwrap()) as usize;
+        let available = data.len() - OVERHEAD;
+        if len > available {
             return Err(AlgorithmError {
-                what: format!("Cannot hide text of length {} bits in {}-line container",
-                              len_bits(text), container.len()
-                )
+                what: format!("Corrupted payload: length is {} bytes, but the container holds at most {}", len, available)
             });
         }
 
-        for s in container.iter_mut() {
-            *s = s.trim_end().to_string();
+        let payload = &data[HEADER_SIZE..HEADER_SIZE + len];
+        if !utils::constant_time_eq(&tag(payload), &data[HEADER_SIZE + len..OVERHEAD + len]) {
+            return Err(AlgorithmError { what: "Corrupted payload: checksum mismatch".to_string() });
         }
+        Ok(payload.to_vec())
+    }
+}
 
-        iterate_bits(text).enumerate().for_each(
-            |(i, bit)| if bit { container[i].push(secret_char) }
-        );
 
-        Ok(container.join("\n"))
+/// Splits a line from `str::split_inclusive('\n')` into its contents and terminator:
+/// `"\r\n"`, `"\n"` or nothing for the last line.
+fn split_terminator(line: &str) -> (&str, &str) {
+    let contents = line.strip_suffix('\n').map_or(line, |l| l.strip_suffix('\r').unwrap_or(l));
+    line.split_at(contents.len())
+}
+
+/// Parses a character written as is or as `U+XXXX`
+fn parse_char(s: &str) -> Result<char, AlgorithmError> {
+    let error = || AlgorithmError { what: format!("Invalid character: {}", s) };
+
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => {
+            let code = s.strip_prefix("U+").or_else(|| s.strip_prefix("u+")).ok_or_else(error)?;
+            u32::from_str_radix(code, 16).ok().and_then(std::char::from_u32).ok_or_else(error)
+        },
     }
+}
 
-    /// Reveal text hidden in multi-line container
-    pub fn reveal(container: &Vec<String>, secret_char: char) -> Result<String, AlgorithmError> {
-        let mut buf = Vec::with_capacity(container.len());
 
-        accumulate_bits(
-            container
-                .iter()
-                .map(|s| s.chars().rev().next().map_or(false, |c| c == secret_char)),
-            &mut buf
-        );
+/// Description of a hiding scheme
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    /// Name used to select the scheme, see `carrier_by_name`
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// A way to hide bits in a text container. Implementors work with bits only; hiding and
+/// revealing arbitrary bytes on top of them is provided, with the payload framed (see `frame`).
+pub trait Carrier {
+    fn metadata(&self) -> Metadata;
+
+    /// Number of bits the container can hold
+    fn capacity(&self, container: &str) -> usize;
+
+    /// Bits each line of the container contributes to `capacity`
+    fn capacity_by_line(&self, container: &str) -> Vec<usize> {
+        container.split_terminator('\n').map(|line| self.capacity(line)).collect()
+    }
+
+    /// Number of payload bytes the container can hold, after framing overhead
+    fn payload_capacity(&self, container: &str) -> usize {
+        (self.capacity(container) / BITS_IN_BYTE as usize).saturating_sub(frame::OVERHEAD)
+    }
+
+    /// Returns the container with bits embedded, failing if they don't fit.
+    fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError>;
+
+    /// Returns all bits the container holds, `capacity` of them.
+    fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError>;
+
+    /// Returns the container with the payload embedded, failing if it doesn't fit.
+    fn embed_payload(&self, container: &str, payload: &[u8]) -> Result<String, AlgorithmError> {
+        let framed = frame::wrap(payload)?;
+        self.embed_bits(container, &iterate_byte_bits(&framed).collect::<Vec<bool>>())
+    }
+
+    /// Returns exactly the payload embedded in the container.
+    fn extract_payload(&self, container: &str) -> Result<Vec<u8>, AlgorithmError> {
+        let mut buf = Vec::new();
+        accumulate_bits(self.extract_bits(container)?.into_iter(), &mut buf);
+        frame::unwrap(&buf)
+    }
+
+    /// `embed_payload` for text
+    fn embed(&self, container: &str, text: &str) -> Result<String, AlgorithmError> {
+        self.embed_payload(container, text.as_bytes())
+    }
+
+    /// `extract_payload` for text, replacing invalid UTF-8
+    fn extract(&self, container: &str) -> Result<String, AlgorithmError> {
+        Ok(String::from_utf8_lossy(&self.extract_payload(container)?).to_string())
+    }
+
+    /// Encrypts the payload with a passphrase (see `container::seal`) and embeds the result.
+    /// Sealing adds `container::HEADER_SIZE + container::TAG_SIZE` bytes to the payload.
+    fn embed_sealed(&self, container: &str, payload: &[u8], passphrase: &str, params: &KdfParams) -> Result<String, AlgorithmError> {
+        let sealed = crate::container::seal(passphrase.as_bytes(), payload, params)?;
+        self.embed_payload(container, &sealed)
+    }
+
+    /// Extracts the payload embedded with `embed_sealed`, failing on a wrong passphrase.
+    fn extract_sealed(&self, container: &str, passphrase: &str) -> Result<Vec<u8>, AlgorithmError> {
+        crate::container::open(passphrase.as_bytes(), &self.extract_payload(container)?)
+    }
+}
+
+pub const CARRIER_NAMES: [&str; 5] = ["lines", "homoglyphs", "zero_width", "spaces", "line_endings"];
 
-        remove_trailing_zeros(&mut buf);
+/// Creates a scheme by name, optionally followed by a scheme parameter after a colon:
+/// `lines[:<secret char>|snow[,<bits per group>[,<groups per line>]]]`, `homoglyphs[:<options>]` where options are a comma-separated
+/// direction (`en_ru` or `ru_en`) and a homoglyph table name or file,
+/// `zero_width[:<bits per symbol>[,<symbols per word boundary>]]`,
+/// `spaces[:<bits per gap>|<comma-separated space characters>]`, `line_endings`.
+pub fn carrier_by_name(spec: &str) -> Result<Box<dyn Carrier>, AlgorithmError> {
+    let (name, parameter) = match spec.split_once(':') {
+        Some((name, parameter)) => (name, Some(parameter)),
+        None => (spec, None),
+    };
+
+    Ok(match name.to_lowercase().as_str() {
+        "lines" => match parameter.map(|p| p.split(',').collect::<Vec<_>>()) {
+            Some(options) if options[0].eq_ignore_ascii_case("snow") => {
+                let number = |i: usize, default: usize| options.get(i).map_or(Ok(default), |o| o.parse().map_err(|_|
+                    AlgorithmError { what: format!("Invalid snow option: {}", o) }
+                ));
+                if options.len() > 3 {
+                    return Err(AlgorithmError { what: "Too many snow options".to_string() });
+                }
+                Box::new(lines::Lines::snow(
+                    number(1, lines::DEFAULT_BITS_PER_GROUP)?,
+                    number(2, lines::DEFAULT_GROUPS_PER_LINE)?,
+                )?)
+            },
+            _ => {
+                let secret_char = match parameter.map(|p| p.chars().collect::<Vec<_>>()) {
+                    None => lines::DEFAULT_SECRET_CHAR,
+                    Some(chars) if chars.len() == 1 => chars[0],
+                    Some(_) => return Err(AlgorithmError { what: "Secret char must be a single character".to_string() }),
+                };
+                Box::new(lines::Lines::new(secret_char))
+            },
+        },
+        "homoglyphs" | "ru_en_similarity" => {
+            let mut carrier = ru_en_similarity::Homoglyphs::new(ru_en_similarity::MappingDirection::EnRu);
+            for option in parameter.into_iter().flat_map(|p| p.split(',')) {
+                match option.parse() {
+                    Ok(direction) => carrier.direction = direction,
+                    Err(()) => carrier.table = ru_en_similarity::HomoglyphTable::load(option)?,
+                }
+            }
+            Box::new(carrier)
+        },
+        "zero_width" => {
+            let mut options = parameter.into_iter().flat_map(|p| p.split(',')).map(|option|
+                option.parse::<usize>().map_err(|_| AlgorithmError { what: format!("Invalid zero_width option: {}", option) })
+            );
+            let bits_per_symbol = options.next().transpose()?.unwrap_or(zero_width::DEFAULT_BITS_PER_SYMBOL);
+            let symbols_per_boundary = options.next().transpose()?.unwrap_or(1);
+            if options.next().is_some() {
+                return Err(AlgorithmError { what: "Too many zero_width options".to_string() });
+            }
+            Box::new(zero_width::ZeroWidth::new(bits_per_symbol, symbols_per_boundary)?)
+        },
+        "spaces" => Box::new(match parameter {
+            None => spaces::Spaces::new(spaces::DEFAULT_BITS_PER_GAP)?,
+            Some(p) => match p.parse::<usize>() {
+                Ok(bits_per_gap) => spaces::Spaces::new(bits_per_gap)?,
+                Err(_) => spaces::Spaces::with_symbols(p.split(',').map(parse_char).collect::<Result<_, _>>()?)?,
+            },
+        }),
+        "line_endings" => match parameter {
+            None => Box::new(line_endings::LineEndings),
+            Some(p) => return Err(AlgorithmError { what: format!("line_endings takes no parameter, got {}", p) }),
+        },
+        other => return Err(AlgorithmError {
+            what: format!("Unknown hiding scheme: {} (known schemes: {})", other, CARRIER_NAMES.join(", "))
+        }),
+    })
+}
+
+
+/// Reads a container file. Carriers work on text, so a container which is not UTF-8 is an error.
+pub fn read_container(path: &str) -> Result<String, AlgorithmError> {
+    let bytes = std::fs::read(path).map_err(|e| AlgorithmError { what: format!("Cannot read container {}: {}", path, e) })?;
+    String::from_utf8(bytes).map_err(|e| AlgorithmError {
+        what: format!("Container {} is not UTF-8 text (invalid byte at offset {})", path, e.utf8_error().valid_up_to())
+    })
+}
 
-        Ok(String::from_utf8_lossy(&buf).to_string())
+/// Hides a payload with any carrier, sealing it with `passphrase` first if one is given.
+pub fn hide_payload(carrier: &dyn Carrier, container: &str, payload: &[u8], passphrase: Option<&str>) -> Result<String, AlgorithmError> {
+    match passphrase {
+        Some(p) => carrier.embed_sealed(container, payload, p, &KdfParams::default()),
+        None => carrier.embed_payload(container, payload),
+    }
+}
+
+/// Reveals a payload hidden with `hide_payload` using the same carrier and passphrase.
+pub fn reveal_payload(carrier: &dyn Carrier, container: &str, passphrase: Option<&str>) -> Result<Vec<u8>, AlgorithmError> {
+    match passphrase {
+        Some(p) => carrier.extract_sealed(container, p),
+        None => carrier.extract_payload(container),
+    }
+}
+
+
+/// Command-line hiding: `Encrypt` hides the input (a file, or the text itself if there is no
+/// such file) in the container, `Decrypt` reveals the payload; the result is written verbatim.
+pub fn hide_or_reveal(carrier: &dyn Carrier, mode: &OperationMode, container_file: &str, input: &str,
+                      output_file: Option<String>, passphrase: Option<&str>) -> Result<(), AlgorithmError> {
+    let container = read_container(container_file)?;
+    let output = match mode {
+        OperationMode::Encrypt => {
+            let payload = std::fs::read(input).unwrap_or_else(|_| input.as_bytes().to_vec());
+            hide_payload(carrier, &container, &payload, passphrase)?.into_bytes()
+        },
+        OperationMode::Decrypt => reveal_payload(carrier, &container, passphrase)?,
+    };
+    utils::write_output(&output, output_file);
+    Ok(())
+}
+
+
+pub mod lines {
+    use super::*;
+
+    pub const DEFAULT_SECRET_CHAR: char = ' ';
+    pub const DEFAULT_BITS_PER_GROUP: usize = 3;
+    pub const DEFAULT_GROUPS_PER_LINE: usize = 2;
+    pub const MAX_BITS_PER_GROUP: usize = 4;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Mode {
+        /// One bit per line: set bits are marked with `secret_char` at the end of the line
+        SingleBit,
+        /// Like SNOW: every line ends with `groups_per_line` groups, each holding a value of
+        /// `bits_per_group` bits as that many spaces followed by a tab
+        Snow { bits_per_group: usize, groups_per_line: usize },
+    }
+
+    /// Bits in trailing whitespace of lines. Lines that get payload bits are trimmed before
+    /// embedding, so their trailing whitespace is lost; lines after the payload and all line
+    /// terminators are kept as they are.
+    pub struct Lines {
+        pub secret_char: char,
+        pub mode: Mode,
+    }
+
+    impl Lines {
+
+        pub fn new(secret_char: char) -> Self {
+            Lines { secret_char, mode: Mode::SingleBit }
+        }
+
+        pub fn snow(bits_per_group: usize, groups_per_line: usize) -> Result<Self, AlgorithmError> {
+            if bits_per_group == 0 || bits_per_group > MAX_BITS_PER_GROUP {
+                return Err(AlgorithmError {
+                    what: format!("Snow groups carry from 1 to {} bits, not {}", MAX_BITS_PER_GROUP, bits_per_group)
+                });
+            }
+            if groups_per_line == 0 {
+                return Err(AlgorithmError { what: "At least one group per line is needed".to_string() });
+            }
+            Ok(Lines { secret_char: DEFAULT_SECRET_CHAR, mode: Mode::Snow { bits_per_group, groups_per_line } })
+        }
+
+        fn bits_per_line(&self) -> usize {
+            match self.mode {
+                Mode::SingleBit => 1,
+                Mode::Snow { bits_per_group, groups_per_line } => bits_per_group * groups_per_line,
+            }
+        }
+    }
+
+    /// Values of snow groups at the end of the line, or nothing if its trailing whitespace
+    /// isn't made of groups.
+    fn snow_groups(line: &str, bits_per_group: usize) -> Vec<usize> {
+        let trailing = &line[line.trim_end().len()..];
+        match trailing.strip_suffix('\t') {
+            Some(groups) => {
+                let values = groups.split('\t').map(|g| g.len()).collect::<Vec<_>>();
+                let valid = groups.split('\t').all(|g| g.chars().all(|c| c == ' ') && g.len() < 1 << bits_per_group);
+                if valid { values } else { Vec::new() }
+            },
+            None => Vec::new(),
+        }
+    }
+
+    impl Carrier for Lines {
+        fn metadata(&self) -> Metadata {
+            match self.mode {
+                Mode::SingleBit => Metadata { name: "lines", description: "one bit per line, marked with a trailing character" },
+                Mode::Snow { .. } => Metadata { name: "lines", description: "bits per line in trailing spaces and tabs, SNOW style" },
+            }
+        }
+
+        fn capacity(&self, container: &str) -> usize {
+            container.split_terminator('\n').count() * self.bits_per_line()
+        }
+
+        fn capacity_by_line(&self, container: &str) -> Vec<usize> {
+            vec![self.bits_per_line(); container.split_terminator('\n').count()]
+        }
+
+        fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError> {
+            check_capacity(self, container, bits.len())?;
+
+            let mut chunks = bits.chunks(self.bits_per_line());
+            let mut result = String::with_capacity(container.len() + bits.len());
+            for line in container.split_inclusive('\n') {
+                let chunk = match chunks.next() {
+                    Some(chunk) => chunk,
+                    None => {
+                        result.push_str(line);
+                        continue;
+                    },
+                };
+                let (line, terminator) = split_terminator(line);
+                result.push_str(line.trim_end());
+                match (self.mode, chunk) {
+                    (Mode::SingleBit, &[true]) => result.push(self.secret_char),
+                    (Mode::Snow { bits_per_group, .. }, chunk) => {
+                        for group in chunk.chunks(bits_per_group) {
+                            result.push_str(&" ".repeat(bits_to_value(group)));
+                            result.push('\t');
+                        }
+                    },
+                    _ => {},
+                }
+                result.push_str(terminator);
+            }
+
+            Ok(result)
+        }
+
+        fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError> {
+            let lines = container.split_inclusive('\n').map(|line| split_terminator(line).0);
+            Ok(match self.mode {
+                Mode::SingleBit => lines.map(|s| s.ends_with(self.secret_char)).collect(),
+                Mode::Snow { bits_per_group, .. } => lines
+                    .flat_map(|line| snow_groups(line, bits_per_group))
+                    .flat_map(|value| value_to_bits(value, bits_per_group))
+                    .collect(),
+            })
+        }
+    }
+
+    /// Hide text in the multi-line container
+    pub fn hide(text: &str, container: &mut [String], secret_char: char) -> Result<String, AlgorithmError> {
+        let result = Lines::new(secret_char).embed(&container.join("\n"), text)?;
+        for (line, hidden) in container.iter_mut().zip(result.split('\n')) {
+            *line = hidden.to_string();
+        }
+        Ok(result)
+    }
+
+    /// Reveal text hidden in multi-line container
+    pub fn reveal(container: &[String], secret_char: char) -> Result<String, AlgorithmError> {
+        Lines::new(secret_char).extract(&container.join("\n"))
     }
 
     #[cfg(test)]
@@ -104,7 +494,7 @@ pub mod lines {
         pub fn test_hide_reveal() {
             let text = "ыs";
             let mut container = "line\n"
-                .repeat(len_bits(text) + 1)
+                .repeat(frame::framed_len_bits(text.len()) + 1)
                 .trim()
                 .split_ascii_whitespace()
                 .map(|s| s.to_string())
@@ -113,7 +503,8 @@ pub mod lines {
 
             assert!(hide(text, &mut container, secret_char).is_ok());
 
-            for (line, bit) in container.iter().zip(iterate_bits(text)) {
+            let framed = frame::wrap(text.as_bytes()).unwrap();
+            for (line, bit) in container.iter().zip(iterate_byte_bits(&framed)) {
                 assert!(!bit || line.ends_with(secret_char))
             }
 
@@ -122,6 +513,54 @@ pub mod lines {
             assert!(decoded.is_ok());
             assert_eq!(decoded.unwrap(), text);
         }
+
+        #[test]
+        pub fn test_keeps_line_endings() {
+            let container = "first  \r\nsecond\nthird\r\nfourth\n";
+            let carrier = Lines::new('*');
+            assert_eq!(carrier.embed_bits(container, &[true, false, true]).unwrap(), "first*\r\nsecond\nthird*\r\nfourth\n");
+            assert_eq!(carrier.extract_bits("first*\r\nsecond\nthird*\r\nfourth\n").unwrap(), [true, false, true, false]);
+        }
+
+        #[test]
+        pub fn test_keeps_lines_after_payload() {
+            let container = "keep  \n".repeat(200);
+            for carrier in [Lines::new(' '), Lines::snow(3, 2).unwrap()].iter() {
+                let hidden = carrier.embed(&container, "x").unwrap();
+                let used = frame::framed_len_bits(1).div_ceil(carrier.bits_per_line());
+                assert!(hidden.split_inclusive('\n').skip(used).eq(container.split_inclusive('\n').skip(used)));
+                assert_eq!(carrier.extract(&hidden).unwrap(), "x");
+            }
+        }
+
+        #[test]
+        pub fn test_snow() {
+            let container = "A line of cover text\t \n".repeat(30);
+            let carrier = Lines::snow(3, 2).unwrap();
+            assert_eq!(carrier.capacity(&container), 30 * 6);
+            assert_eq!(carrier.capacity_by_line("a\nb\n"), vec![6, 6]);
+
+            let hidden = carrier.embed(&container, "snow").unwrap();
+            assert_eq!(carrier.extract(&hidden).unwrap(), "snow");
+            assert_eq!(carrier.extract(&hidden.replace('\n', "\r\n")).unwrap(), "snow");
+            assert!(hidden.lines().all(|line| line.starts_with("A line of cover text")));
+
+            // payload bits run out inside the last group
+            assert_eq!(carrier.embed_bits("a\nb\nc", &[true, false, true, true]).unwrap(), "a     \t \t\nb\nc");
+            assert_eq!(carrier.extract_bits("a     \t \t\nb\nc").unwrap(), [true, false, true, true, false, false]);
+
+            assert!(Lines::snow(0, 1).is_err());
+            assert!(Lines::snow(MAX_BITS_PER_GROUP + 1, 1).is_err());
+            assert!(Lines::snow(1, 0).is_err());
+        }
+
+        #[test]
+        pub fn test_snow_ignores_foreign_whitespace() {
+            assert!(snow_groups("text  ", 3).is_empty());
+            assert_eq!(snow_groups("text \t  x\t", 3), [0]);
+            assert!(snow_groups("text        \t", 3).is_empty());
+            assert_eq!(snow_groups("text \t\t   \t", 3), [1, 0, 3]);
+        }
     }
 }
 
@@ -159,91 +598,251 @@ pub mod ru_en_similarity {
         }
     }
 
-    // Not present as a static variable because there are constraints on compile-time code
-    fn create_char_map(dir: MappingDirection) -> HashMap<char, char> {
-        let map = vec![
-            // (En char, Cyr char)
-            // lowercase
-            ('a', 'а'),
-            ('c', 'с'),
-            ('e', 'е'),
-            ('o', 'о'),
-            ('p', 'р'),
-            ('y', 'у'),
-            // uppercase
-            ('A', 'А'),
-            ('B', 'В'),
-            ('C', 'С'),
-            ('E', 'Е'),
-            ('H', 'Н'),
-            ('K', 'К'),
-            ('M', 'М'),
-            ('O', 'О'),
-            ('P', 'Р'),
-            ('T', 'Т'),
-            ('X', 'Х'),
-        ];
+    /// (En char, Cyr char)
+    const BASIC_PAIRS: [(char, char); 17] = [
+        // lowercase
+        ('a', 'а'),
+        ('c', 'с'),
+        ('e', 'е'),
+        ('o', 'о'),
+        ('p', 'р'),
+        ('y', 'у'),
+        // uppercase
+        ('A', 'А'),
+        ('B', 'В'),
+        ('C', 'С'),
+        ('E', 'Е'),
+        ('H', 'Н'),
+        ('K', 'К'),
+        ('M', 'М'),
+        ('O', 'О'),
+        ('P', 'Р'),
+        ('T', 'Т'),
+        ('X', 'Х'),
+    ];
 
-        match dir {
-            MappingDirection::EnRu => map.into_iter().collect(),
-            MappingDirection::RuEn => map.into_iter().map(|(c1, c2)| (c2, c1)).collect(),
-        }
+    /// Cyrillic look-alikes on top of `BASIC_PAIRS`, mostly from Ukrainian and Serbian alphabets
+    const EXTRA_CYRILLIC_PAIRS: [(char, char); 8] = [
+        ('h', 'һ'),
+        ('i', 'і'),
+        ('j', 'ј'),
+        ('s', 'ѕ'),
+        ('x', 'х'),
+        ('I', 'І'),
+        ('J', 'Ј'),
+        ('S', 'Ѕ'),
+    ];
+
+    /// (En char, Greek char)
+    const GREEK_PAIRS: [(char, char); 18] = [
+        ('a', 'α'),
+        ('o', 'ο'),
+        ('p', 'ρ'),
+        ('v', 'ν'),
+        ('A', 'Α'),
+        ('B', 'Β'),
+        ('E', 'Ε'),
+        ('H', 'Η'),
+        ('I', 'Ι'),
+        ('K', 'Κ'),
+        ('M', 'Μ'),
+        ('N', 'Ν'),
+        ('O', 'Ο'),
+        ('P', 'Ρ'),
+        ('T', 'Τ'),
+        ('X', 'Χ'),
+        ('Y', 'Υ'),
+        ('Z', 'Ζ'),
+    ];
+
+    /// (ASCII char, Latin-1 Supplement char)
+    const LATIN1_PAIRS: [(char, char); 2] = [
+        (' ', '\u{a0}'),
+        ('x', '×'),
+    ];
+
+    pub const TABLE_NAMES: [&str; 5] = ["basic", "cyrillic", "greek", "latin1", "extended"];
+
+    /// Pairs of a plain character and its look-alike. Every character appears in a table at most
+    /// once, so that both hiding and revealing are unambiguous.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HomoglyphTable {
+        pairs: Vec<(char, char)>,
     }
 
-    /// Hide text in the container
-    pub fn hide(text: &str, container: &str, direction: MappingDirection) -> Result<String, AlgorithmError> {
-        let map = create_char_map(direction);
-        let inverse_map = create_char_map(direction.invert());
+    impl HomoglyphTable {
 
-        if container.chars().any(|c| inverse_map.contains_key(&c)) {
-            return Err(AlgorithmError {
-                what: "Container is dirty".to_string()
-            })
+        /// Checks that the pairs form a bijection between two disjoint sets of characters.
+        pub fn new(pairs: Vec<(char, char)>) -> Result<Self, AlgorithmError> {
+            if pairs.is_empty() {
+                return Err(AlgorithmError { what: "Homoglyph table is empty".to_string() });
+            }
+
+            if let Some(&(plain, _)) = pairs.iter().find(|(plain, lookalike)| plain == lookalike) {
+                return Err(AlgorithmError { what: format!("Homoglyph table maps {:?} to itself", plain) });
+            }
+
+            let mut seen = HashMap::new();
+            for (i, &(plain, lookalike)) in pairs.iter().enumerate() {
+                for c in [plain, lookalike].iter() {
+                    if let Some(j) = seen.insert(*c, i) {
+                        return Err(AlgorithmError {
+                            what: format!("Homoglyph table is not a bijection: {:?} is in pairs {} and {}", c, j + 1, i + 1)
+                        });
+                    }
+                }
+            }
+
+            Ok(HomoglyphTable { pairs })
         }
 
-        let mut bits = iterate_bits(text);
+        /// Built-in table by name, see `TABLE_NAMES`
+        pub fn by_name(name: &str) -> Result<Self, AlgorithmError> {
+            let pairs = match name.to_lowercase().as_str() {
+                "basic" => BASIC_PAIRS.to_vec(),
+                "cyrillic" => [&BASIC_PAIRS[..], &EXTRA_CYRILLIC_PAIRS[..]].concat(),
+                "greek" => GREEK_PAIRS.to_vec(),
+                "latin1" => LATIN1_PAIRS.to_vec(),
+                // 'x' already has a Cyrillic look-alike
+                "extended" => [&BASIC_PAIRS[..], &EXTRA_CYRILLIC_PAIRS[..], &LATIN1_PAIRS[..1]].concat(),
+                other => return Err(AlgorithmError {
+                    what: format!("Unknown homoglyph table: {} (known tables: {})", other, TABLE_NAMES.join(", "))
+                }),
+            };
+            HomoglyphTable::new(pairs)
+        }
 
-        let result = container
-            .chars()
-            .map(|c|
-                map
-                    .get(&c)
-                    .map_or(c, |cc| if bits.next().unwrap_or(false) { *cc } else { c })
-            )
-            .collect::<String>();
+        /// Built-in table by name, or a table file otherwise
+        pub fn load(name_or_path: &str) -> Result<Self, AlgorithmError> {
+            if TABLE_NAMES.contains(&name_or_path.to_lowercase().as_str()) {
+                return HomoglyphTable::by_name(name_or_path);
+            }
+            std::fs::read_to_string(name_or_path)
+                .map_err(|e| AlgorithmError { what: format!("Can't read homoglyph table {}: {}", name_or_path, e) })?
+                .parse()
+        }
 
-        if bits.next().is_none() {
-            Ok(result)
-        } else {
-            Err(AlgorithmError {
-                what: "Container is too small to hide given information".to_string()
-            })
+        pub fn pairs(&self) -> &[(char, char)] {
+            &self.pairs
+        }
+
+        /// Maps plain characters to look-alikes for `EnRu`, and the other way round for `RuEn`.
+        pub fn char_map(&self, dir: MappingDirection) -> HashMap<char, char> {
+            match dir {
+                MappingDirection::EnRu => self.pairs.iter().copied().collect(),
+                MappingDirection::RuEn => self.pairs.iter().map(|&(c1, c2)| (c2, c1)).collect(),
+            }
         }
     }
 
-    /// Reveal text hidden in the container
-    pub fn reveal(container: &str, direction: MappingDirection) -> Result<String, AlgorithmError> {
-        let map = create_char_map(direction);
-        let inverse_map = create_char_map(direction.invert());
-
-        let result = container
-            .chars()
-            .filter_map(|c| {
-                if map.contains_key(&c) {
-                    Some(false)
-                } else if inverse_map.contains_key(&c) {
-                    Some(true)
-                } else {
-                    None
+    impl Default for HomoglyphTable {
+        fn default() -> Self {
+            HomoglyphTable { pairs: BASIC_PAIRS.to_vec() }
+        }
+    }
+
+    /// Table file has a pair per line: a plain character and its look-alike, separated by
+    /// whitespace. Characters are either written as is or as `U+XXXX`; `#` starts a comment.
+    impl FromStr for HomoglyphTable {
+        type Err = AlgorithmError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let mut pairs = Vec::new();
+            for (i, line) in s.lines().enumerate() {
+                let line = match line.find(" #") {
+                    Some(comment) => &line[..comment],
+                    None if line.trim_start().starts_with('#') => "",
+                    None => line,
+                };
+                let tokens = line.split_whitespace().collect::<Vec<_>>();
+                match tokens.as_slice() {
+                    [] => continue,
+                    [plain, lookalike] => pairs.push((parse_char(plain)?, parse_char(lookalike)?)),
+                    _ => return Err(AlgorithmError {
+                        what: format!("Invalid homoglyph table line {}: expected two characters", i + 1)
+                    }),
                 }
-            });
+            }
+            HomoglyphTable::new(pairs)
+        }
+    }
 
-        let mut buf = Vec::new();
-        accumulate_bits(result, &mut buf);
+    /// One bit per letter that has a look-alike in the table: set bits replace the letter with
+    /// its look-alike.
+    pub struct Homoglyphs {
+        pub direction: MappingDirection,
+        pub table: HomoglyphTable,
+    }
+
+    impl Homoglyphs {
 
-        remove_trailing_zeros(&mut buf);
+        /// Uses the basic Latin/Cyrillic table
+        pub fn new(direction: MappingDirection) -> Self {
+            Homoglyphs { direction, table: HomoglyphTable::default() }
+        }
+
+    }
+
+    impl Carrier for Homoglyphs {
+        fn metadata(&self) -> Metadata {
+            Metadata { name: "homoglyphs", description: "one bit per letter, swapped with a look-alike of the other alphabet" }
+        }
+
+        fn capacity(&self, container: &str) -> usize {
+            let map = self.table.char_map(self.direction);
+            container.chars().filter(|c| map.contains_key(c)).count()
+        }
+
+        fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError> {
+            let map = self.table.char_map(self.direction);
+            let inverse_map = self.table.char_map(self.direction.invert());
+
+            if container.chars().any(|c| inverse_map.contains_key(&c)) {
+                return Err(AlgorithmError {
+                    what: "Container is dirty".to_string()
+                })
+            }
+            check_capacity(self, container, bits.len())?;
+
+            let mut bits = bits.iter();
+
+            Ok(container
+                .chars()
+                .map(|c|
+                    map
+                        .get(&c)
+                        .map_or(c, |cc| if bits.next().copied().unwrap_or(false) { *cc } else { c })
+                )
+                .collect::<String>())
+        }
+
+        fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError> {
+            let map = self.table.char_map(self.direction);
+            let inverse_map = self.table.char_map(self.direction.invert());
+
+            Ok(container
+                .chars()
+                .filter_map(|c| {
+                    if map.contains_key(&c) {
+                        Some(false)
+                    } else if inverse_map.contains_key(&c) {
+                        Some(true)
+                    } else {
+                        None
+                    }
+                })
+                .collect())
+        }
+    }
 
-        Ok(String::from_utf8_lossy(&buf).to_string())
+    /// Hide text in the container
+    pub fn hide(text: &str, container: &str, direction: MappingDirection) -> Result<String, AlgorithmError> {
+        Homoglyphs::new(direction).embed(container, text)
+    }
+
+    /// Reveal text hidden in the container
+    pub fn reveal(container: &str, direction: MappingDirection) -> Result<String, AlgorithmError> {
+        Homoglyphs::new(direction).extract(container)
     }
 
     #[cfg(test)]
@@ -253,7 +852,7 @@ pub mod ru_en_similarity {
         #[test]
         pub fn test_hide_reveal() {
             let text = "ыs";
-            let container = "a".repeat(len_bits(text) + 1);
+            let container = "a".repeat(frame::framed_len_bits(text.len()) + 1);
 
             let container = hide(text, &container, MappingDirection::EnRu);
             assert!(container.is_ok());
@@ -263,5 +862,536 @@ pub mod ru_en_similarity {
             assert!(decoded.is_ok());
             assert_eq!(decoded.unwrap(), text);
         }
+
+        #[test]
+        pub fn test_builtin_tables() {
+            for name in TABLE_NAMES.iter() {
+                assert!(HomoglyphTable::by_name(name).is_ok(), "{}", name);
+            }
+            assert_eq!(HomoglyphTable::by_name("basic").unwrap(), HomoglyphTable::default());
+            assert!(HomoglyphTable::by_name("klingon").is_err());
+
+            let container = "six boxes of jam, sir\n".repeat(12);
+            let basic = Homoglyphs::new(MappingDirection::EnRu);
+            let extended = Homoglyphs { direction: MappingDirection::EnRu, table: HomoglyphTable::by_name("extended").unwrap() };
+            assert!(extended.capacity(&container) > 2 * basic.capacity(&container));
+
+            let hidden = extended.embed(&container, "hi").unwrap();
+            assert_eq!(extended.extract(&hidden).unwrap(), "hi");
+            assert!(hidden.contains('\u{a0}'));
+        }
+
+        #[test]
+        pub fn test_table_file() {
+            let table = "# plain, look-alike\n\
+                         a а\n\
+                         U+0020 U+00A0 # no-break space\n\
+                         \n\
+                         o ο\n".parse::<HomoglyphTable>().unwrap();
+            assert_eq!(table.pairs(), &[('a', 'а'), (' ', '\u{a0}'), ('o', 'ο')]);
+
+            let carrier = Homoglyphs { direction: MappingDirection::EnRu, table };
+            assert_eq!(carrier.capacity("a b o"), 4);
+
+            assert!("a а b".parse::<HomoglyphTable>().is_err());
+            assert!("ab а".parse::<HomoglyphTable>().is_err());
+            assert!("U+D800 а".parse::<HomoglyphTable>().is_err());
+            assert!("".parse::<HomoglyphTable>().is_err());
+        }
+
+        #[test]
+        pub fn test_table_bijection() {
+            assert!(HomoglyphTable::new(vec![('a', 'а'), ('o', 'о')]).is_ok());
+            assert!(HomoglyphTable::new(vec![('a', 'а'), ('a', 'α')]).is_err());
+            assert!(HomoglyphTable::new(vec![('a', 'а'), ('e', 'а')]).is_err());
+            assert!(HomoglyphTable::new(vec![('a', 'b'), ('b', 'c')]).is_err());
+            assert_eq!(HomoglyphTable::new(vec![('a', 'a')]).unwrap_err().what, "Homoglyph table maps 'a' to itself");
+        }
+    }
+}
+
+
+pub mod zero_width {
+    use super::*;
+
+    /// Zero width space, non-joiner, joiner and word joiner. With one bit per symbol only the
+    /// first two are used.
+    pub const SYMBOLS: [char; 4] = ['\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}'];
+    pub const DEFAULT_BITS_PER_SYMBOL: usize = 2;
+
+    /// Hides bits in zero width characters inserted at word boundaries, that is right after the
+    /// last character of a word. Extraction only looks at the zero width characters themselves,
+    /// so it survives editors that keep them but change whitespace, line endings or add a BOM.
+    pub struct ZeroWidth {
+        bits_per_symbol: usize,
+        symbols_per_boundary: usize,
+    }
+
+    fn is_boundary(prev: Option<char>, next: char) -> bool {
+        next.is_whitespace() && prev.is_some_and(|c| !c.is_whitespace())
+    }
+
+    impl ZeroWidth {
+
+        pub fn new(bits_per_symbol: usize, symbols_per_boundary: usize) -> Result<Self, AlgorithmError> {
+            if bits_per_symbol != 1 && bits_per_symbol != 2 {
+                return Err(AlgorithmError { what: format!("Zero width symbols carry 1 or 2 bits, not {}", bits_per_symbol) });
+            }
+            if symbols_per_boundary == 0 {
+                return Err(AlgorithmError { what: "At least one symbol per word boundary is needed".to_string() });
+            }
+            Ok(ZeroWidth { bits_per_symbol, symbols_per_boundary })
+        }
+
+        fn alphabet(&self) -> &'static [char] {
+            &SYMBOLS[..1 << self.bits_per_symbol]
+        }
+
+        fn boundaries(container: &str) -> usize {
+            let mut prev = None;
+            let mut count = 0;
+            for c in container.chars() {
+                if is_boundary(prev, c) {
+                    count += 1;
+                }
+                prev = Some(c);
+            }
+            count
+        }
+    }
+
+    impl Carrier for ZeroWidth {
+        fn metadata(&self) -> Metadata {
+            Metadata { name: "zero_width", description: "bits in zero width characters inserted after words" }
+        }
+
+        fn capacity(&self, container: &str) -> usize {
+            ZeroWidth::boundaries(container) * self.symbols_per_boundary * self.bits_per_symbol
+        }
+
+        fn capacity_by_line(&self, container: &str) -> Vec<usize> {
+            container.split_inclusive('\n').map(|line| self.capacity(line)).collect()
+        }
+
+        fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError> {
+            if container.chars().any(|c| SYMBOLS.contains(&c)) {
+                return Err(AlgorithmError { what: "Container is dirty".to_string() });
+            }
+            check_capacity(self, container, bits.len())?;
+
+            let alphabet = self.alphabet();
+            let mut symbols = bits.chunks(self.bits_per_symbol).map(|chunk| alphabet[bits_to_value(chunk)]);
+
+            let mut result = String::with_capacity(container.len() + bits.len() * 3);
+            let mut prev = None;
+            for c in container.chars() {
+                if is_boundary(prev, c) {
+                    result.extend(symbols.by_ref().take(self.symbols_per_boundary));
+                }
+                result.push(c);
+                prev = Some(c);
+            }
+            Ok(result)
+        }
+
+        fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError> {
+            let alphabet = self.alphabet();
+            Ok(container
+                .chars()
+                .filter_map(|c| alphabet.iter().position(|&s| s == c))
+                .flat_map(|value| value_to_bits(value, self.bits_per_symbol))
+                .collect())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        pub fn test_hide_reveal() {
+            let container = "Nothing to see here, move along.\n".repeat(40);
+            for &(bits, per_boundary) in [(1, 1), (2, 1), (2, 3)].iter() {
+                let carrier = ZeroWidth::new(bits, per_boundary).unwrap();
+                assert_eq!(carrier.capacity(&container), 6 * 40 * bits * per_boundary);
+
+                let hidden = carrier.embed(&container, "hi").unwrap();
+                assert_eq!(hidden.chars().filter(|c| !SYMBOLS.contains(c)).collect::<String>(), container);
+                assert_eq!(carrier.extract(&hidden).unwrap(), "hi");
+                assert!(carrier.embed(&hidden, "hi").is_err());
+            }
+
+            assert!(ZeroWidth::new(3, 1).is_err());
+            assert!(ZeroWidth::new(1, 0).is_err());
+        }
+
+        #[test]
+        pub fn test_placement() {
+            let carrier = ZeroWidth::new(1, 1).unwrap();
+            assert_eq!(carrier.capacity("  two words \n"), 2);
+            assert_eq!(carrier.embed_bits("  two words \n", &[true, false]).unwrap(),
+                       "  two\u{200c} words\u{200b} \n");
+            assert_eq!(carrier.capacity_by_line("a b\nc\n\nd e f"), vec![2, 1, 0, 2]);
+        }
+
+        #[test]
+        pub fn test_editor_round_trip() {
+            let carrier = ZeroWidth::new(2, 1).unwrap();
+            let hidden = carrier.embed(&"Some words in a line\n".repeat(30), "payload").unwrap();
+
+            let edited = format!("\u{feff}{}", hidden.replace('\n', "\r\n").replace("  ", " ").replace(' ', "\u{a0}"));
+            assert_eq!(carrier.extract(&edited).unwrap(), "payload");
+        }
+    }
+}
+
+
+pub mod spaces {
+    use super::*;
+
+    /// Regular space first, then other spaces roughly ordered by how close they look to it:
+    /// no-break, four-per-em, three-per-em, thin, narrow no-break, six-per-em and en space.
+    pub const SYMBOLS: [char; 8] = [' ', '\u{a0}', '\u{2005}', '\u{2004}', '\u{2009}', '\u{202f}', '\u{2006}', '\u{2002}'];
+    pub const DEFAULT_BITS_PER_GAP: usize = 2;
+
+    /// Hides bits by replacing single regular spaces between words with one of `2^k` space
+    /// characters, `k` bits per gap.
+    pub struct Spaces {
+        symbols: Vec<char>,
+        bits_per_gap: usize,
+    }
+
+    impl Spaces {
+
+        /// Uses the first `2^bits_per_gap` of `SYMBOLS`
+        pub fn new(bits_per_gap: usize) -> Result<Self, AlgorithmError> {
+            if bits_per_gap == 0 || 1 << bits_per_gap > SYMBOLS.len() {
+                return Err(AlgorithmError {
+                    what: format!("Built-in spaces carry from 1 to 3 bits per gap, not {}", bits_per_gap)
+                });
+            }
+            Spaces::with_symbols(SYMBOLS[..1 << bits_per_gap].to_vec())
+        }
+
+        /// Symbol `i` encodes value `i`; their number must be a power of two.
+        pub fn with_symbols(symbols: Vec<char>) -> Result<Self, AlgorithmError> {
+            if symbols.len() < 2 || !symbols.len().is_power_of_two() {
+                return Err(AlgorithmError {
+                    what: format!("Number of space symbols must be a power of two, not {}", symbols.len())
+                });
+            }
+            if let Some(c) = symbols.iter().find(|c| !c.is_whitespace()) {
+                return Err(AlgorithmError { what: format!("Not a space character: {:?}", c) });
+            }
+            for (i, c) in symbols.iter().enumerate() {
+                if symbols[..i].contains(c) {
+                    return Err(AlgorithmError { what: format!("Space symbol {:?} is repeated", c) });
+                }
+            }
+
+            let bits_per_gap = symbols.len().trailing_zeros() as usize;
+            Ok(Spaces { symbols, bits_per_gap })
+        }
+
+        pub fn symbols(&self) -> &[char] {
+            &self.symbols
+        }
+
+        /// Calls `f` with the index of every character that is a single space of `is_space`
+        /// between two non-space characters.
+        fn for_each_gap<F: FnMut(usize)>(chars: &[char], is_space: impl Fn(char) -> bool, mut f: F) {
+            for i in 1..chars.len().saturating_sub(1) {
+                if is_space(chars[i]) && !chars[i - 1].is_whitespace() && !chars[i + 1].is_whitespace() {
+                    f(i);
+                }
+            }
+        }
+    }
+
+    impl Carrier for Spaces {
+        fn metadata(&self) -> Metadata {
+            Metadata { name: "spaces", description: "bits in look-alike Unicode spaces between words" }
+        }
+
+        fn capacity(&self, container: &str) -> usize {
+            let chars = container.chars().collect::<Vec<char>>();
+            let mut gaps = 0;
+            Spaces::for_each_gap(&chars, |c| c == ' ', |_| gaps += 1);
+            gaps * self.bits_per_gap
+        }
+
+        fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError> {
+            if container.chars().any(|c| c != ' ' && self.symbols.contains(&c)) {
+                return Err(AlgorithmError { what: "Container is dirty".to_string() });
+            }
+            check_capacity(self, container, bits.len())?;
+
+            let mut chars = container.chars().collect::<Vec<char>>();
+            let mut chunks = bits.chunks(self.bits_per_gap);
+            let mut gaps = Vec::new();
+            Spaces::for_each_gap(&chars, |c| c == ' ', |i| gaps.push(i));
+            for i in gaps {
+                match chunks.next() {
+                    Some(chunk) => chars[i] = self.symbols[bits_to_value(chunk)],
+                    None => break,
+                }
+            }
+            Ok(chars.into_iter().collect())
+        }
+
+        fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError> {
+            let chars = container.chars().collect::<Vec<char>>();
+            let mut bits = Vec::new();
+            Spaces::for_each_gap(&chars, |c| self.symbols.contains(&c), |i| {
+                let value = self.symbols.iter().position(|&s| s == chars[i]).unwrap();
+                bits.extend(value_to_bits(value, self.bits_per_gap));
+            });
+            Ok(bits)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        pub fn test_hide_reveal() {
+            let container = "Words are separated by spaces, mostly.\n".repeat(20);
+            for bits in 1..=3 {
+                let carrier = Spaces::new(bits).unwrap();
+                assert_eq!(carrier.capacity(&container), 5 * 20 * bits);
+
+                let hidden = carrier.embed(&container, "hi").unwrap();
+                assert_eq!(hidden.chars().count(), container.chars().count());
+                assert_eq!(carrier.extract(&hidden).unwrap(), "hi");
+            }
+
+            assert!(Spaces::new(0).is_err());
+            assert!(Spaces::new(4).is_err());
+            assert!(Spaces::new(1).unwrap().embed("a\u{a0}b c", "").is_err());
+        }
+
+        #[test]
+        pub fn test_gaps() {
+            let carrier = Spaces::new(1).unwrap();
+            assert_eq!(carrier.capacity(" a b  c\td e \n f"), 2);
+            assert_eq!(carrier.embed_bits(" a b  c\td e \n f", &[true, false]).unwrap(), " a\u{a0}b  c\td e \n f");
+            assert_eq!(carrier.capacity_by_line("a b c\nd\ne f"), vec![2, 0, 1]);
+        }
+
+        #[test]
+        pub fn test_custom_symbols() {
+            let carrier = Spaces::with_symbols(vec![' ', '\u{2009}', '\u{2002}', '\u{3000}']).unwrap();
+            let container = "one two three four five six seven eight\n".repeat(10);
+            let hidden = carrier.embed(&container, "x").unwrap();
+            assert!(hidden.chars().all(|c| c.is_ascii() || carrier.symbols().contains(&c)));
+            assert_eq!(carrier.extract(&hidden).unwrap(), "x");
+
+            assert!(Spaces::with_symbols(vec![' ', '\u{a0}', '\u{2009}']).is_err());
+            assert!(Spaces::with_symbols(vec![' ', '_']).is_err());
+            assert!(Spaces::with_symbols(vec![' ', ' ']).is_err());
+        }
+    }
+}
+
+
+pub mod line_endings {
+    use super::*;
+
+    /// One bit per line terminator: `\r\n` for set bits and `\n` for clear ones. Everything
+    /// else in the container, including terminators after the payload, is kept byte for byte.
+    pub struct LineEndings;
+
+    impl Carrier for LineEndings {
+        fn metadata(&self) -> Metadata {
+            Metadata { name: "line_endings", description: "one bit per line terminator, CRLF or LF" }
+        }
+
+        fn capacity(&self, container: &str) -> usize {
+            container.matches('\n').count()
+        }
+
+        fn capacity_by_line(&self, container: &str) -> Vec<usize> {
+            container.split_inclusive('\n').map(|line| self.capacity(line)).collect()
+        }
+
+        fn embed_bits(&self, container: &str, bits: &[bool]) -> Result<String, AlgorithmError> {
+            check_capacity(self, container, bits.len())?;
+
+            let mut bits = bits.iter();
+            let mut result = String::with_capacity(container.len() + bits.len());
+            for line in container.split_inclusive('\n') {
+                let (contents, terminator) = split_terminator(line);
+                // "\r\r\n" would read back as a set bit whatever is written
+                if !terminator.is_empty() && contents.ends_with('\r') {
+                    return Err(AlgorithmError { what: "Container is dirty".to_string() });
+                }
+                result.push_str(contents);
+                result.push_str(match (terminator, bits.next()) {
+                    ("", _) | (_, None) => terminator,
+                    (_, Some(true)) => "\r\n",
+                    (_, Some(false)) => "\n",
+                });
+            }
+            Ok(result)
+        }
+
+        fn extract_bits(&self, container: &str) -> Result<Vec<bool>, AlgorithmError> {
+            Ok(container
+                .split_inclusive('\n')
+                .map(|line| split_terminator(line).1)
+                .filter(|terminator| !terminator.is_empty())
+                .map(|terminator| terminator == "\r\n")
+                .collect())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        pub fn test_hide_reveal() {
+            let container = "Line one\n".repeat(120);
+            let hidden = LineEndings.embed(&container, "crlf").unwrap();
+            assert_eq!(hidden.replace("\r\n", "\n"), container);
+            assert_eq!(LineEndings.extract(&hidden).unwrap(), "crlf");
+            assert!(LineEndings.embed(&container, "too long").is_err());
+        }
+
+        #[test]
+        pub fn test_byte_accurate() {
+            let container = "  indented\t \r\nplain\n\n\r\nlast line without terminator  ";
+            assert_eq!(LineEndings.capacity(container), 4);
+            assert_eq!(LineEndings.capacity_by_line(container), vec![1, 1, 1, 1, 0]);
+
+            let hidden = LineEndings.embed_bits(container, &[false, true]).unwrap();
+            assert_eq!(hidden, "  indented\t \nplain\r\n\n\r\nlast line without terminator  ");
+            assert_eq!(LineEndings.extract_bits(&hidden).unwrap(), [false, true, false, true]);
+            assert_eq!(LineEndings.embed_bits(container, &[]).unwrap(), container);
+        }
+
+        #[test]
+        pub fn test_dirty_container() {
+            let container = "a\r\r\nb\n".repeat(60);
+            assert_eq!(LineEndings.embed_bits(&container, &[false]).unwrap_err().what, "Container is dirty");
+            assert!(LineEndings.embed(&container, "x").is_err());
+            assert!(LineEndings.embed_bits("a\nlast line\r", &[true]).is_ok());
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_carriers_by_name() {
+        let container = "Some text for a cover,\nwith a few lines\nin it\n".repeat(40);
+        for spec in ["lines", "lines:\t", "homoglyphs", "homoglyphs:en_ru", "homoglyphs:greek", "homoglyphs:extended,en_ru",
+                     "zero_width", "zero_width:1", "zero_width:2,2",
+                     "spaces", "spaces:3", "spaces:U+0020,U+2009",
+                     "lines:snow", "lines:snow,2", "lines:snow,4,3", "line_endings"].iter() {
+            let carrier = carrier_by_name(spec).unwrap();
+            assert!(carrier.capacity(&container) >= len_bits("hi"), "{}", spec);
+
+            let hidden = carrier.embed(&container, "hi").unwrap();
+            assert_eq!(carrier.extract(&hidden).unwrap(), "hi", "{}", spec);
+            assert!(carrier.embed(&container, &"x".repeat(carrier.capacity(&container))).is_err());
+        }
+
+        assert!(carrier_by_name("lines:ab").is_err());
+        assert!(carrier_by_name("lines:snow,9").is_err());
+        assert!(carrier_by_name("lines:snow,3,x").is_err());
+        assert!(carrier_by_name("line_endings:x").is_err());
+        assert!(carrier_by_name("homoglyphs:xx").is_err());
+        assert!(carrier_by_name("homoglyphs:en_ru,/nonexistent/table").is_err());
+        assert!(carrier_by_name("zero_width:3").is_err());
+        assert!(carrier_by_name("zero_width:1,2,3").is_err());
+        assert!(carrier_by_name("spaces:4").is_err());
+        assert!(carrier_by_name("spaces:U+0020,x").is_err());
+        assert!(carrier_by_name("unknown").is_err());
+    }
+
+    #[test]
+    pub fn test_capacity() {
+        let container = "Some text\n\nfor a cover\n".repeat(40);
+        let lines = carrier_by_name("lines").unwrap();
+        assert_eq!(lines.capacity_by_line(&container), vec![1; 120]);
+        assert_eq!(lines.payload_capacity(&container), 15 - frame::OVERHEAD);
+
+        let homoglyphs = carrier_by_name("homoglyphs").unwrap();
+        assert_eq!(homoglyphs.capacity_by_line(&container)[..3], [3, 0, 5]);
+        assert_eq!(homoglyphs.payload_capacity(&container), 40 - frame::OVERHEAD);
+        assert_eq!(homoglyphs.payload_capacity("short"), 0);
+
+        let payload = "x".repeat(lines.payload_capacity(&container));
+        assert!(lines.embed(&container, &payload).is_ok());
+        assert!(lines.embed(&container, &(payload + "x")).is_err());
+    }
+
+    #[test]
+    pub fn test_bit_groups() {
+        assert_eq!(bits_to_value(&[true, false, true]), 5);
+        assert_eq!(value_to_bits(5, 4).collect::<Vec<_>>(), [true, false, true, false]);
+        assert_eq!(bits_to_value(&value_to_bits(6, 3).collect::<Vec<_>>()), 6);
+
+        let error = lines::Lines::new(' ').embed_bits("a\nb\n", &[true; 3]).unwrap_err();
+        assert_eq!(error.what, "Cannot hide 3 bits in a container of 2 bits (lines)");
+    }
+
+    #[test]
+    pub fn test_framing() {
+        let payload = "ends with NULs\0\0";
+        assert_eq!(frame::unwrap(&frame::wrap(payload.as_bytes()).unwrap()).unwrap(), payload.as_bytes());
+        assert_eq!(frame::unwrap(&frame::wrap(b"").unwrap()).unwrap(), b"");
+
+        let container = "line\n".repeat(frame::framed_len_bits(payload.len()) + 10);
+        let carrier = lines::Lines::new('*');
+        let hidden = carrier.embed(&container, payload).unwrap();
+        assert_eq!(carrier.extract(&hidden).unwrap(), payload);
+
+        let error = carrier.extract(&container).unwrap_err();
+        assert!(error.what.contains("no hidden payload"), "{}", error.what);
+
+        // flip a bit inside the payload
+        let mut corrupted = hidden.split('\n').map(|l| l.to_string()).collect::<Vec<_>>();
+        let i = frame::HEADER_SIZE * 8 + 3;
+        corrupted[i] = if corrupted[i].ends_with('*') { "line".to_string() } else { "line*".to_string() };
+        let error = carrier.extract(&corrupted.join("\n")).unwrap_err();
+        assert!(error.what.contains("checksum"), "{}", error.what);
+
+        // claim a length longer than the container
+        let mut framed = frame::wrap(b"abc").unwrap();
+        framed[2] = 0xff;
+        assert!(frame::unwrap(&framed).unwrap_err().what.contains("length"));
+    }
+
+    #[test]
+    pub fn test_binary_payload() {
+        let payload = (0..=255u8).rev().chain([0, 0, 0].iter().copied()).collect::<Vec<u8>>();
+        let container = "Binary data, byte by byte, goes in here\n".repeat(2500);
+        for spec in CARRIER_NAMES.iter() {
+            let carrier = carrier_by_name(spec).unwrap();
+            let hidden = carrier.embed_payload(&container, &payload).unwrap();
+            assert_eq!(carrier.extract_payload(&hidden).unwrap(), payload, "{}", spec);
+            assert_eq!(carrier.extract_payload(&carrier.embed_payload(&container, &[]).unwrap()).unwrap(), b"");
+        }
+    }
+
+    #[test]
+    pub fn test_sealed() {
+        const PARAMS: KdfParams = KdfParams { iterations: 10 };
+        let container = "Cover text with some letters to spare: a, c, e, o, p\n".repeat(800);
+        for spec in CARRIER_NAMES.iter() {
+            let carrier = carrier_by_name(spec).unwrap();
+            let hidden = carrier.embed_sealed(&container, b"attack at dawn", "passphrase", &PARAMS).unwrap();
+
+            assert_eq!(carrier.extract_sealed(&hidden, "passphrase").unwrap(), b"attack at dawn", "{}", spec);
+            let error = carrier.extract_sealed(&hidden, "wrong").unwrap_err();
+            assert!(error.what.contains("Wrong passphrase"), "{}", error.what);
+            assert!(carrier.extract_sealed(&carrier.embed(&container, "plain").unwrap(), "passphrase").is_err());
+        }
     }
 }
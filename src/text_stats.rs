@@ -4,6 +4,20 @@ type StrCounts<'a> = HashMap<&'a str, u64>;
 
 type StrFrequencies<'a> = Vec<(&'a str, f64)>;
 
+/// Reference letter frequencies of English text, in alphabetical order
+pub const ENGLISH_FREQUENCIES: [f64; 26] = [
+    0.08167, 0.01492, 0.02782, 0.04253, 0.12702, 0.02228, 0.02015, 0.06094, 0.06966,
+    0.00153, 0.00772, 0.04025, 0.02406, 0.06749, 0.07507, 0.01929, 0.00095, 0.05987,
+    0.06327, 0.09056, 0.02758, 0.00978, 0.02360, 0.00150, 0.01974, 0.00074,
+];
+
+/// Reference letter frequencies of Russian text, in alphabetical order (ё after е)
+pub const RUSSIAN_FREQUENCIES: [f64; 33] = [
+    0.0801, 0.0159, 0.0454, 0.0170, 0.0298, 0.0845, 0.0004, 0.0094, 0.0165, 0.0735, 0.0121,
+    0.0349, 0.0440, 0.0321, 0.0670, 0.1097, 0.0281, 0.0473, 0.0547, 0.0626, 0.0262, 0.0026,
+    0.0097, 0.0048, 0.0144, 0.0073, 0.0036, 0.0004, 0.0190, 0.0174, 0.0032, 0.0064, 0.0201,
+];
+
 /// Builds a sliding window iterator for a given string.
 /// Equivalent of s.chars().collect::<Vec<char>>().window(win), but does not allocate.
 pub fn slide_string(s: &str, win: usize) -> impl Iterator<Item=&str> {
@@ -16,7 +30,7 @@ pub fn slide_string(s: &str, win: usize) -> impl Iterator<Item=&str> {
 /// Computes counts of characters or character clusters in text, with respect to filter.
 /// Filter is applied as following: if all chars in window satisfy filter, then keep this window,
 /// else skip it (this helps to respect word boundaries, for example).
-pub fn counts<F>(text: &str, filter: F, win: usize) -> (u64, StrCounts)
+pub fn counts<F>(text: &str, filter: F, win: usize) -> (u64, StrCounts<'_>)
     where F: Fn(char,) -> bool
 {
     let mut freq = StrCounts::with_capacity(1 << 8);
@@ -33,7 +47,7 @@ pub fn counts<F>(text: &str, filter: F, win: usize) -> (u64, StrCounts)
 }
 
 /// Computes frequencies of characters or character clusters in text.
-pub fn frequencies<F>(text: &str, filter: F, win: usize) -> StrFrequencies
+pub fn frequencies<F>(text: &str, filter: F, win: usize) -> StrFrequencies<'_>
     where F: Fn(char,) -> bool
 {
     let (total, freq) = counts(text, filter, win);
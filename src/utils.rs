@@ -1,6 +1,6 @@
 use std::str::FromStr;
 use std::path::{Path, PathBuf};
-use std::io;
+use std::io::{self, Write};
 
 /// Some executables in this course need to distinguish between Encoding and Decoding modes
 pub enum OperationMode {
@@ -20,11 +20,34 @@ impl FromStr for OperationMode {
     }
 }
 
+/// Removes `--name <value>` from command line arguments, returning the value
+pub fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let i = args.iter().position(|a| a == name)?;
+    if i + 1 >= args.len() {
+        panic!("Option {} needs a value", name);
+    }
+    args.remove(i);
+    Some(args.remove(i))
+}
+
+/// Writes output verbatim to a file, or to stdout if no file is given: hidden containers keep
+/// their line endings and trailing whitespace, and revealed payloads may be binary.
+pub fn write_output(output: &[u8], output_file: Option<String>) {
+    match output_file {
+        Some(filename) => std::fs::write(filename, output).expect("Can't write output file"),
+        None => io::stdout().write_all(output).expect("Can't write output"),
+    }
+}
+
+/// Error reported by the algorithms in this crate when they can't process given input.
+#[derive(Debug)]
+pub struct AlgorithmError { pub what: String }
+
 /// Detects whether an UTF-8 character belongs to a modern Russian character range.
 #[inline]
 pub fn is_russian_char(c: char) -> bool {
     let c: u32 = c.into();
-    (0x0410 <= c && c < 0x0450) || c == 0x0401 || c == 0x0451
+    (0x0410..0x0450).contains(&c) || c == 0x0401 || c == 0x0451
 }
 
 /// List all files in directory tree into vector.
@@ -45,3 +68,61 @@ pub fn files_in_tree(root: &Path) -> io::Result<Vec<PathBuf>> {
     accumulate_files(root, &mut result)?;
     Ok(result)
 }
+
+/// Parses a hex string leniently (any case, whitespace, `0x` prefix and `:` separators allowed).
+pub fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    crate::codec::hex::decode(s, crate::codec::Strictness::Lenient).map_err(|e| e.what)
+}
+
+/// Formats bytes as a lowercase hex string.
+pub fn to_hex(data: &[u8]) -> String {
+    crate::codec::hex::encode(data)
+}
+
+/// Reads `n` random bytes from the OS CSPRNG.
+pub fn random_bytes(n: usize) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut buf = vec![0u8; n];
+    std::fs::File::open("/dev/urandom")?.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Compares two byte slices without short-circuiting on the first difference.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Small non-cryptographic PRNG (xorshift64*), for randomized algorithms which need to be
+/// reproducible from a seed. Use `random_bytes` for anything secret.
+#[derive(Debug, Clone)]
+pub struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    pub fn new(seed: u64) -> Xorshift64 {
+        // splitmix64 step, so that similar seeds give unrelated sequences and zero state is avoided
+        let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        Xorshift64 { state: (z ^ (z >> 31)) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// Uniformly distributed number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as usize
+    }
+
+    /// Uniformly distributed number in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -1,15 +1,79 @@
 use std::iter::Iterator;
 
+const WORD_SIZE: usize = std::mem::size_of::<u64>();
+
+/// Minimal length of the repeated key block, so that short keys still give long inner loops
+const MIN_BLOCK_SIZE: usize = 512;
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
 /// Performs XOR encoding on an array of bytes, using a given key.
+/// Key must not be empty; see `cipher::Xor` for a checked version.
+///
+/// Bytes are processed a u64 word at a time: the key is repeated into a block whose length is
+/// a multiple of both key length and word size, so that every aligned word of data is XORed with
+/// a precomputed word of the key. The inner loop over a block is simple enough to be vectorized
+/// by the compiler.
 pub fn xor_inplace(bytes: &mut [u8], key: &[u8]) {
-    for (i, b) in bytes.iter_mut().enumerate() {
+    assert!(bytes.is_empty() || !key.is_empty(), "XOR key must not be empty");
+    if bytes.is_empty() {
+        return;
+    }
+
+    // a key block longer than the data would never be reused, and for keys as long as the
+    // data it could take several times their size: XOR such data byte by byte
+    let period = key.len() / gcd(key.len(), WORD_SIZE) * WORD_SIZE;
+    let block_size = period * MIN_BLOCK_SIZE.div_ceil(period);
+    if block_size > bytes.len() {
+        bytes.iter_mut().zip(key.iter().cycle()).for_each(|(b, k)| *b ^= k);
+        return;
+    }
+
+    // SAFETY: any bit pattern is a valid u64
+    let (head, words, tail) = unsafe { bytes.align_to_mut::<u64>() };
+
+    for (i, b) in head.iter_mut().enumerate() {
         *b ^= key[i % key.len()];
     }
+
+    if !words.is_empty() {
+        let offset = head.len();
+
+        let key_words = (0..block_size)
+            .map(|i| key[(offset + i) % key.len()])
+            .collect::<Vec<u8>>()
+            .chunks_exact(WORD_SIZE)
+            .map(|w| {
+                let mut word = [0u8; WORD_SIZE];
+                word.copy_from_slice(w);
+                u64::from_ne_bytes(word)
+            })
+            .collect::<Vec<u64>>();
+
+        for block in words.chunks_mut(key_words.len()) {
+            block.iter_mut().zip(key_words.iter()).for_each(|(w, k)| *w ^= k);
+        }
+    }
+
+    let offset = head.len() + words.len() * WORD_SIZE;
+    for (i, b) in tail.iter_mut().enumerate() {
+        *b ^= key[(offset + i) % key.len()];
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test::Bencher;
+
+    /// Reference byte-at-a-time implementation
+    fn xor_inplace_naive(bytes: &mut [u8], key: &[u8]) {
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b ^= key[i % key.len()];
+        }
+    }
 
     #[test]
     pub fn test_xor_applied_twice() {
@@ -22,4 +86,57 @@ mod tests {
 
         assert_eq!(String::from_utf8_lossy(&text), "some text".to_string());
     }
+
+    #[test]
+    pub fn test_xor_matches_naive() {
+        let data = (0..1000).map(|i| (i * 31 % 251) as u8).collect::<Vec<u8>>();
+
+        for key_len in [1, 2, 3, 7, 8, 9, 16, 24, 100, 1001, 1 << 16].iter() {
+            let key = (0..*key_len).map(|i| (i * 17 + 5) as u8).collect::<Vec<u8>>();
+
+            // different start offsets and lengths shift the data relative to word alignment
+            for start in 0..WORD_SIZE {
+                for end in [start, start + 1, start + 13, 500, 1000].iter() {
+                    let mut fast = data[start..*end].to_vec();
+                    let mut naive = fast.clone();
+
+                    xor_inplace(&mut fast, &key);
+                    xor_inplace_naive(&mut naive, &key);
+
+                    assert_eq!(fast, naive, "key length {}, data {}..{}", key_len, start, end);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_xor_empty_key() {
+        xor_inplace(&mut [1, 2, 3], &[]);
+    }
+
+    #[bench]
+    pub fn bench_xor_inplace_1mb_key_3(b: &mut Bencher) {
+        let mut data = vec![0x5au8; 1 << 20];
+        b.iter(|| xor_inplace(&mut data, b"abc"))
+    }
+
+    #[bench]
+    pub fn bench_xor_inplace_naive_1mb_key_3(b: &mut Bencher) {
+        let mut data = vec![0x5au8; 1 << 20];
+        b.iter(|| xor_inplace_naive(&mut data, b"abc"))
+    }
+
+    #[bench]
+    pub fn bench_xor_inplace_1mb_key_32(b: &mut Bencher) {
+        let mut data = vec![0x5au8; 1 << 20];
+        b.iter(|| xor_inplace(&mut data, b"YELLOW SUBMARINE YELLOW SUBMARIN"))
+    }
+
+    #[bench]
+    pub fn bench_xor_inplace_1mb_key_1000(b: &mut Bencher) {
+        let mut data = vec![0x5au8; 1 << 20];
+        let key = (0..1000).map(|i| i as u8).collect::<Vec<u8>>();
+        b.iter(|| xor_inplace(&mut data, &key))
+    }
 }